@@ -0,0 +1,307 @@
+//! Adaptive Replacement Cache
+
+use alloc::boxed::Box;
+
+use crate::{
+    list::{Link, Links, List},
+    tag_index::TagIndex,
+};
+
+/// A fixed-capacity table that evicts according to the Adaptive Replacement Cache policy
+///
+/// Recency and frequency are tracked in separate lists whose relative sizes adapt to the workload,
+/// guided by ghost entries that remember the tags of recently evicted values. Tags are supplied by
+/// the caller on insert and should identify the value, e.g. by hashing its key; inserting a tag
+/// that was recently evicted is treated as evidence that the cache is favoring the wrong list.
+///
+/// Ghosts occupy slots without storing values, so at most `2 * capacity` slots are in use.
+pub struct ArcSlab<T> {
+    slots: Box<[Slot<T>]>,
+    free: List,
+    /// Entries accessed once since they last became resident
+    t1: List,
+    /// Entries accessed at least twice since they last became resident
+    t2: List,
+    /// Ghosts evicted from `t1`
+    b1: List,
+    /// Ghosts evicted from `t2`
+    b2: List,
+    /// Locates ghosts by tag
+    ghosts: TagIndex,
+    /// Adaptive target length of `t1`
+    target: u32,
+    capacity: u32,
+}
+
+impl<T> ArcSlab<T> {
+    /// Create an [`ArcSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        let len = capacity.checked_mul(2).expect("capacity too large");
+        assert!(len != u32::MAX, "capacity too large");
+        let mut slots = (0..len)
+            .map(|_| Slot {
+                value: None,
+                tag: 0,
+                link: Link::UNLINKED,
+                queue: Queue::Free,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..len {
+            free.push_back(&mut slots[..], slot);
+        }
+        Self {
+            slots,
+            free,
+            t1: List::EMPTY,
+            t2: List::EMPTY,
+            b1: List::EMPTY,
+            b2: List::EMPTY,
+            ghosts: TagIndex::new(capacity),
+            target: 0,
+            capacity,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.t1.len + self.t2.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Number of values the cache is currently aiming to hold in its recency list
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    /// Insert a value identified by `tag`, returning the slot it was stored in and the slot and
+    /// value of any entry evicted to make room
+    pub fn insert(&mut self, tag: u64, value: T) -> (u32, Option<(u32, T)>) {
+        if let Some(slot) = self.ghosts.remove(tag) {
+            // Ghost hit: the list it was evicted from deserves more space
+            let from_b2 = match self.slots[slot as usize].queue {
+                Queue::B1 => {
+                    let delta = (self.b2.len / self.b1.len).max(1);
+                    self.target = (self.target + delta).min(self.capacity);
+                    self.b1.remove(&mut self.slots[..], slot);
+                    false
+                }
+                Queue::B2 => {
+                    let delta = (self.b1.len / self.b2.len).max(1);
+                    self.target = self.target.saturating_sub(delta);
+                    self.b2.remove(&mut self.slots[..], slot);
+                    true
+                }
+                _ => unreachable!("corrupt ghost index"),
+            };
+            let evicted = self.replace_if_full(from_b2);
+            self.occupy(slot, tag, value, Queue::T2);
+            return (slot, evicted);
+        }
+
+        let mut evicted = None;
+        if self.t1.len + self.b1.len == self.capacity {
+            if self.t1.len < self.capacity {
+                self.forget_oldest(Queue::B1);
+                evicted = self.replace_if_full(false);
+            } else {
+                // The recency side is entirely resident; evict without leaving a ghost
+                let slot = self.t1.pop_back(&mut self.slots[..]).unwrap();
+                evicted = Some((slot, self.release(slot)));
+            }
+        } else if self.t1.len + self.t2.len + self.b1.len + self.b2.len >= self.capacity {
+            if self.b1.len + self.b2.len + self.len() == 2 * self.capacity {
+                self.forget_oldest(Queue::B2);
+            }
+            evicted = self.replace_if_full(false);
+        }
+        let slot = self
+            .free
+            .pop_front(&mut self.slots[..])
+            .expect("no free slots");
+        self.occupy(slot, tag, value, Queue::T1);
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    ///
+    /// No ghost is left behind.
+    pub fn remove(&mut self, slot: u32) -> T {
+        match self.slots[slot as usize].queue {
+            Queue::T1 => self.t1.remove(&mut self.slots[..], slot),
+            Queue::T2 => self.t2.remove(&mut self.slots[..], slot),
+            _ => panic!("removing empty slot"),
+        }
+        self.release(slot)
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        match self.slots[slot as usize].queue {
+            Queue::T1 => self.t1.remove(&mut self.slots[..], slot),
+            Queue::T2 => self.t2.remove(&mut self.slots[..], slot),
+            _ => panic!("accessing empty slot"),
+        }
+        self.t2.push_front(&mut self.slots[..], slot);
+        self.slots[slot as usize].queue = Queue::T2;
+        self.peek_mut(slot)
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Turn a resident entry into a ghost if no more values fit
+    fn replace_if_full(&mut self, from_b2: bool) -> Option<(u32, T)> {
+        if self.len() < self.capacity {
+            return None;
+        }
+        let prefer_t1 = self.t1.len != 0
+            && ((from_b2 && self.t1.len == self.target) || self.t1.len > self.target);
+        let (slot, queue) = if (prefer_t1 || self.t2.is_empty()) && !self.t1.is_empty() {
+            let slot = self.t1.pop_back(&mut self.slots[..]).unwrap();
+            self.b1.push_front(&mut self.slots[..], slot);
+            (slot, Queue::B1)
+        } else {
+            let slot = self.t2.pop_back(&mut self.slots[..]).unwrap();
+            self.b2.push_front(&mut self.slots[..], slot);
+            (slot, Queue::B2)
+        };
+        let entry = &mut self.slots[slot as usize];
+        entry.queue = queue;
+        let value = entry.value.take().unwrap();
+        if let Some(stale) = self.ghosts.insert(entry.tag, slot) {
+            // The same tag was evicted before; only the newest ghost is meaningful
+            self.forget(stale);
+        }
+        Some((slot, value))
+    }
+
+    /// Discard the least recently evicted ghost from `queue`
+    fn forget_oldest(&mut self, queue: Queue) {
+        let slot = match queue {
+            Queue::B1 => self.b1.back(),
+            Queue::B2 => self.b2.back(),
+            _ => unreachable!(),
+        }
+        .expect("ghost list unexpectedly empty");
+        self.ghosts.remove(self.slots[slot as usize].tag);
+        self.forget(slot);
+    }
+
+    /// Free a ghost slot that has already been removed from the index
+    fn forget(&mut self, slot: u32) {
+        match self.slots[slot as usize].queue {
+            Queue::B1 => self.b1.remove(&mut self.slots[..], slot),
+            Queue::B2 => self.b2.remove(&mut self.slots[..], slot),
+            _ => unreachable!("corrupt ghost index"),
+        }
+        self.slots[slot as usize].queue = Queue::Free;
+        self.free.push_front(&mut self.slots[..], slot);
+    }
+
+    fn occupy(&mut self, slot: u32, tag: u64, value: T, queue: Queue) {
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        entry.value = Some(value);
+        entry.tag = tag;
+        entry.queue = queue;
+        match queue {
+            Queue::T1 => self.t1.push_front(&mut self.slots[..], slot),
+            Queue::T2 => self.t2.push_front(&mut self.slots[..], slot),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Return an unlinked resident slot to the free list, yielding its value
+    fn release(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        entry.queue = Queue::Free;
+        let value = entry.value.take().expect("removing empty slot");
+        self.free.push_front(&mut self.slots[..], slot);
+        value
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    tag: u64,
+    link: Link,
+    queue: Queue,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Queue {
+    Free,
+    T1,
+    T2,
+    B1,
+    B2,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequent_survives_scan() {
+        let mut cache = ArcSlab::new(4);
+        let (hot, _) = cache.insert(0, 0);
+        cache.get_mut(hot);
+        for tag in 1..100 {
+            cache.insert(tag, tag);
+            assert!(cache.len() <= 4);
+        }
+        assert_eq!(*cache.peek(hot), 0);
+    }
+
+    #[test]
+    fn ghost_hit_adapts() {
+        let mut cache = ArcSlab::new(2);
+        let (a, _) = cache.insert(0, 'a');
+        cache.get_mut(a);
+        cache.insert(1, 'b');
+        let (_, evicted) = cache.insert(2, 'c');
+        assert_eq!(evicted.map(|(_, x)| x), Some('b'));
+        assert_eq!(cache.target(), 0);
+        let (slot, evicted) = cache.insert(1, 'b');
+        assert_eq!(evicted.map(|(_, x)| x), Some('a'));
+        assert_eq!(cache.target(), 1);
+        assert_eq!(*cache.peek(slot), 'b');
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn remove() {
+        let mut cache = ArcSlab::new(2);
+        let (a, _) = cache.insert(0, 'a');
+        let (b, _) = cache.insert(1, 'b');
+        assert_eq!(cache.remove(a), 'a');
+        assert_eq!(cache.len(), 1);
+        let (_, evicted) = cache.insert(2, 'c');
+        assert!(evicted.is_none());
+        assert_eq!(*cache.get_mut(b), 'b');
+    }
+}
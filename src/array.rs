@@ -0,0 +1,207 @@
+//! LRU tracking in a fixed-size array, without allocating
+
+use core::mem::MaybeUninit;
+
+use crate::{
+    list::{Link, List},
+    Iter, IterState, FREE, NONE,
+};
+
+/// A random-access table that maintains an LRU list in constant time, holding at most `N`
+/// elements inline
+///
+/// Unlike [`LruSlab`](crate::LruSlab), storage is never allocated, so this works on targets
+/// without a heap, with the default `alloc` feature disabled. Inserting into a full array fails
+/// rather than growing.
+///
+/// [`new`](Self::new) is a `const fn` that doesn't touch individual slots, so an array can be
+/// placed in a `static`, e.g. behind a `critical_section::Mutex<RefCell<_>>`, without any
+/// initialization at startup.
+///
+/// ```
+/// use core::cell::RefCell;
+///
+/// use critical_section::Mutex;
+/// use lru_slab::LruArray;
+///
+/// static CACHE: Mutex<RefCell<LruArray<u32, 16>>> = Mutex::new(RefCell::new(LruArray::new()));
+///
+/// critical_section::with(|cs| {
+///     let mut cache = CACHE.borrow_ref_mut(cs);
+///     let slot = cache.insert(42).unwrap();
+///     assert_eq!(*cache.get_mut(slot), 42);
+/// });
+/// ```
+pub struct LruArray<T, const N: usize> {
+    /// Initialized where the corresponding link is occupied
+    values: [MaybeUninit<T>; N],
+    /// `prev` is `FREE` for vacant slots below `unused`, whose `next` links the free list
+    links: [Link; N],
+    recent: List,
+    /// First slot of the free list
+    free: u32,
+    /// Slots at and above this index have never been occupied, and their links are meaningless
+    unused: u32,
+}
+
+impl<T, const N: usize> LruArray<T, N> {
+    /// Create an empty [`LruArray`]
+    pub const fn new() -> Self {
+        assert!(N < FREE as usize, "capacity too large");
+        Self {
+            values: [const { MaybeUninit::uninit() }; N],
+            links: [Link::UNLINKED; N],
+            recent: List::EMPTY,
+            free: NONE,
+            unused: 0,
+        }
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
+    }
+
+    /// Whether every slot is occupied
+    pub fn is_full(&self) -> bool {
+        self.free == NONE && self.unused as usize == N
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.recent.len
+    }
+
+    /// Maximum number of elements that can be stored
+    pub fn capacity(&self) -> u32 {
+        N as u32
+    }
+
+    /// Insert a value, returning the slot it was stored in, or the value if the array is full
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&mut self, value: T) -> Result<u32, T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        let slot = if self.free != NONE {
+            let slot = self.free;
+            self.free = self.links[slot as usize].next;
+            slot
+        } else {
+            self.unused += 1;
+            self.unused - 1
+        };
+        self.values[slot as usize].write(value);
+        self.recent.push_front(&mut self.links[..], slot);
+        Ok(slot)
+    }
+
+    /// Get the least recently used slot, if any
+    pub fn lru(&self) -> Option<u32> {
+        self.recent.back()
+    }
+
+    /// Remove the least recently used element, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        let slot = self.lru()?;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        assert!(self.contains(slot), "removing empty slot");
+        self.recent.remove(&mut self.links[..], slot);
+        self.links[slot as usize] = Link {
+            prev: FREE,
+            next: self.free,
+        };
+        self.free = slot;
+        // Safety: the slot was occupied, and is now vacant
+        unsafe { self.values[slot as usize].assume_init_read() }
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        assert!(self.contains(slot), "accessing empty slot");
+        if self.recent.head != slot {
+            self.recent.remove(&mut self.links[..], slot);
+            self.recent.push_front(&mut self.links[..], slot);
+        }
+        // Safety: checked above
+        unsafe { self.values[slot as usize].assume_init_mut() }
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u32) -> Option<&T> {
+        // Safety: occupied slots are initialized
+        self.contains(slot)
+            .then(|| unsafe { self.values[slot as usize].assume_init_ref() })
+    }
+
+    /// Whether `slot` is occupied
+    pub fn contains(&self, slot: u32) -> bool {
+        slot < self.unused && self.links[slot as usize].prev != FREE
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> &T {
+        self.get(slot).expect("accessing empty slot")
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        assert!(self.contains(slot), "accessing empty slot");
+        // Safety: checked above
+        unsafe { self.values[slot as usize].assume_init_mut() }
+    }
+
+    /// Walk the container from most to least recently used
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            values: &self.values[..],
+            links: &self.links[..],
+            state: IterState {
+                head: self.recent.head,
+                tail: self.recent.tail,
+                len: self.recent.len,
+            },
+        }
+    }
+}
+
+impl<T, const N: usize> Default for LruArray<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for LruArray<T, N> {
+    fn drop(&mut self) {
+        let unused = self.unused as usize;
+        for (value, link) in self.values[..unused].iter_mut().zip(&self.links) {
+            if link.prev != FREE {
+                // Safety: occupied slots are initialized
+                unsafe { value.assume_init_drop() }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_up() {
+        const EMPTY: LruArray<char, 2> = LruArray::new();
+        let mut array = EMPTY;
+        let a = array.insert('a').unwrap();
+        let b = array.insert('b').unwrap();
+        assert_eq!(array.insert('c'), Err('c'));
+        array.get_mut(a);
+        assert_eq!(array.pop_lru(), Some((b, 'b')));
+        assert_eq!(array.insert('c'), Ok(b));
+        assert_eq!(array.lru(), Some(a));
+    }
+}
@@ -0,0 +1,173 @@
+//! Approximate LRU with lock-free reads
+
+use alloc::boxed::Box;
+
+use crate::{
+    primitives::{AtomicU64, Ordering},
+    rng::Rng,
+    NONE,
+};
+
+/// A fixed-capacity table that approximates LRU eviction using atomic access stamps
+///
+/// Reads through a shared reference only store the current time in the accessed slot, so any
+/// number of threads can read concurrently without relinking a list or contending on a lock. Time
+/// is measured by a clock that ticks on every insert. When full, a handful of slots are sampled at
+/// random and the one with the oldest stamp is evicted.
+///
+/// Mutation requires unique access, e.g. via a `RwLock` whose read side serves lookups.
+pub struct AtomicLruSlab<T> {
+    slots: Box<[Slot<T>]>,
+    len: u32,
+    /// First unused slot
+    free: u32,
+    /// Number of slots examined per eviction
+    samples: u32,
+    clock: u64,
+    rng: Rng,
+}
+
+impl<T> AtomicLruSlab<T> {
+    /// Create an [`AtomicLruSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        Self::with_samples(capacity, DEFAULT_SAMPLES)
+    }
+
+    /// Create an [`AtomicLruSlab`] that holds at most `capacity` values and examines `samples`
+    /// slots per eviction
+    pub fn with_samples(capacity: u32, samples: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        assert!(capacity != u32::MAX, "capacity too large");
+        assert!(samples != 0, "samples must be nonzero");
+        Self {
+            slots: (0..capacity)
+                .map(|n| Slot {
+                    value: None,
+                    stamp: AtomicU64::new(0),
+                    next: if n + 1 == capacity { NONE } else { n + 1 },
+                })
+                .collect(),
+            len: 0,
+            free: 0,
+            samples,
+            clock: 0,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Insert a value, returning the slot it was stored in and the slot and value of any entry
+    /// evicted to make room
+    ///
+    /// When an entry is evicted, the new value takes over its slot.
+    pub fn insert(&mut self, value: T) -> (u32, Option<(u32, T)>) {
+        self.clock += 1;
+        let evicted = match self.free {
+            NONE => {
+                let slot = self.sample();
+                Some((slot, self.remove(slot)))
+            }
+            _ => None,
+        };
+        let slot = self.free;
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        self.free = entry.next;
+        entry.value = Some(value);
+        entry.stamp.store(self.clock, Ordering::Relaxed);
+        self.len += 1;
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        let value = entry.value.take().expect("removing empty slot");
+        entry.next = self.free;
+        self.free = slot;
+        self.len -= 1;
+        value
+    }
+
+    /// Record a use of `slot` and access it
+    pub fn get(&self, slot: u32) -> &T {
+        let entry = &self.slots[slot as usize];
+        let value = entry.value.as_ref().expect("accessing empty slot");
+        entry.stamp.store(self.clock, Ordering::Relaxed);
+        value
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &mut self.slots[slot as usize];
+        entry.stamp.store(self.clock, Ordering::Relaxed);
+        entry.value.as_mut().expect("accessing empty slot")
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Find the least recently used slot among a random sample, when every slot is occupied
+    fn sample(&mut self) -> u32 {
+        let mut best = self.rng.below(self.capacity());
+        for _ in 1..self.samples {
+            let candidate = self.rng.below(self.capacity());
+            if self.stamp(candidate) < self.stamp(best) {
+                best = candidate;
+            }
+        }
+        best
+    }
+
+    fn stamp(&self, slot: u32) -> u64 {
+        self.slots[slot as usize].stamp.load(Ordering::Relaxed)
+    }
+}
+
+/// Number of slots examined per eviction by [`AtomicLruSlab::new`]
+const DEFAULT_SAMPLES: u32 = 5;
+
+struct Slot<T> {
+    value: Option<T>,
+    /// Value of the clock at the slot's last use
+    stamp: AtomicU64,
+    /// Next slot in the free list
+    next: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_oldest_sampled() {
+        let mut cache = AtomicLruSlab::with_samples(4, 64);
+        let slots = [0, 1, 2, 3].map(|x| cache.insert(x).0);
+        for slot in [slots[0], slots[2], slots[3]] {
+            cache.get(slot);
+        }
+        let (_, evicted) = cache.insert(4);
+        assert_eq!(evicted, Some((slots[1], 1)));
+    }
+}
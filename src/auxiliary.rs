@@ -0,0 +1,118 @@
+//! LRU tracking with a second value per slot
+
+use crate::LruSlab;
+
+/// An [`LruSlab`] that stores an auxiliary value of type `U` alongside each element
+///
+/// Auxiliary values are stored, moved, cloned, and dropped together with their elements, but are
+/// accessed separately, and accessing them never affects recency. Suitable for bookkeeping such
+/// as sizes or version numbers that would otherwise need a parallel array kept in sync by hand.
+#[derive(Clone)]
+pub struct AuxLruSlab<T, U> {
+    slab: LruSlab<(T, U)>,
+}
+
+impl<T, U> AuxLruSlab<T, U> {
+    /// Create an empty [`AuxLruSlab`]
+    pub fn new() -> Self {
+        Self {
+            slab: LruSlab::new(),
+        }
+    }
+
+    /// Create an [`AuxLruSlab`] that can store at least `capacity` elements without reallocating
+    pub fn with_capacity(capacity: u32) -> Self {
+        Self {
+            slab: LruSlab::with_capacity(capacity),
+        }
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.slab.is_empty()
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.slab.len()
+    }
+
+    /// Insert a value and its auxiliary value, returning the slot they were stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&mut self, value: T, aux: U) -> u32 {
+        self.slab.insert((value, aux))
+    }
+
+    /// Get the least recently used slot that isn't pinned, if any
+    pub fn lru(&self) -> Option<u32> {
+        self.slab.lru()
+    }
+
+    /// Remove the least recently used element that isn't pinned, returning its slot, value, and
+    /// auxiliary value
+    pub fn pop_lru(&mut self) -> Option<(u32, T, U)> {
+        let (slot, (value, aux)) = self.slab.pop_lru()?;
+        Some((slot, value, aux))
+    }
+
+    /// Remove the element stored in `slot`, returning it and its auxiliary value
+    pub fn remove(&mut self, slot: u32) -> (T, U) {
+        self.slab.remove(slot)
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        &mut self.slab.get_mut(slot).0
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u32) -> Option<&T> {
+        self.slab.get(slot).map(|(value, _)| value)
+    }
+
+    /// Access the auxiliary value of `slot`, if it's occupied
+    pub fn aux(&self, slot: u32) -> Option<&U> {
+        self.slab.get(slot).map(|(_, aux)| aux)
+    }
+
+    /// Access the auxiliary value of `slot` uniquely
+    pub fn aux_mut(&mut self, slot: u32) -> &mut U {
+        &mut self.slab.peek_mut(slot).1
+    }
+
+    /// The underlying slab, whose elements pair values with their auxiliary values
+    pub fn slab(&self) -> &LruSlab<(T, U)> {
+        &self.slab
+    }
+
+    /// Unique access to the underlying slab
+    pub fn slab_mut(&mut self) -> &mut LruSlab<(T, U)> {
+        &mut self.slab
+    }
+}
+
+impl<T, U> Default for AuxLruSlab<T, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aux_follows_slot() {
+        let mut slab = AuxLruSlab::new();
+        let a = slab.insert('a', 1);
+        let b = slab.insert('b', 2);
+        *slab.aux_mut(a) += 10;
+        assert_eq!(slab.lru(), Some(a));
+        assert_eq!(slab.remove(a), ('a', 11));
+        let c = slab.insert('c', 3);
+        assert_eq!(c, a);
+        assert_eq!(slab.aux(c), Some(&3));
+        assert_eq!(slab.aux(b), Some(&2));
+    }
+}
@@ -0,0 +1,174 @@
+//! Compact sets of slot indices
+
+use allocator_api2::{
+    alloc::{Allocator, Global},
+    collections::TryReserveError,
+    vec::Vec,
+};
+use core::{iter, mem};
+
+use crate::extend_reserved;
+
+/// A fixed-size set of slot indices, one bit per slot
+#[derive(Clone)]
+pub(crate) struct BitSet<A: Allocator = Global> {
+    words: Vec<u64, A>,
+}
+
+impl<A: Allocator> BitSet<A> {
+    /// Create an empty set that can't hold any indices until grown
+    pub(crate) const fn new_in(alloc: A) -> Self {
+        Self {
+            words: Vec::new_in(alloc),
+        }
+    }
+
+    /// Extend the set to hold indices less than `len`
+    pub(crate) fn grow(&mut self, len: u32) {
+        self.words
+            .reserve_exact(words_for(len).saturating_sub(self.words.len()));
+        self.grow_reserved(len);
+    }
+
+    /// Extend the set to hold indices less than `len`, within storage already reserved for them
+    pub(crate) fn grow_reserved(&mut self, len: u32) {
+        let additional = words_for(len).saturating_sub(self.words.len());
+        let added = extend_reserved(&mut self.words, iter::repeat_n(0, additional));
+        debug_assert_eq!(added, additional, "storage not reserved");
+    }
+
+    /// Reserve storage for indices less than `len`, so that growing to `len` won't allocate
+    pub(crate) fn try_reserve(&mut self, len: u32) -> Result<(), TryReserveError> {
+        self.words
+            .try_reserve_exact(words_for(len).saturating_sub(self.words.len()))
+    }
+
+    /// Shrink the set to hold indices less than `len`, none of which may be at least `len`
+    pub(crate) fn truncate(&mut self, len: u32) {
+        self.words.truncate(words_for(len));
+        self.words.shrink_to_fit();
+    }
+
+    pub(crate) fn insert(&mut self, index: u32) {
+        debug_assert!(
+            (index as usize) < self.words.len() * 64,
+            "index out of bounds"
+        );
+        if let Some(word) = self.words.get_mut(index as usize / 64) {
+            *word |= 1 << (index % 64);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, index: u32) {
+        debug_assert!(
+            (index as usize) < self.words.len() * 64,
+            "index out of bounds"
+        );
+        if let Some(word) = self.words.get_mut(index as usize / 64) {
+            *word &= !(1 << (index % 64));
+        }
+    }
+
+    /// Indices in the set, in ascending order
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = u32> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &word)| Bits::new(i, word))
+    }
+
+    /// Whether `index` is in the set, which it can't be if the set doesn't hold it
+    pub(crate) fn contains(&self, index: u32) -> bool {
+        self.words
+            .get(index as usize / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    /// Greatest index less than `index` that isn't in the set, if any
+    pub(crate) fn last_absent_below(&self, index: u32) -> Option<u32> {
+        let mut i = index as usize / 64;
+        let mut absent = !*self.words.get(i)? & ((1 << (index % 64)) - 1);
+        loop {
+            if absent != 0 {
+                return Some(i as u32 * 64 + 63 - absent.leading_zeros());
+            }
+            i = i.checked_sub(1)?;
+            absent = !*self.words.get(i)?;
+        }
+    }
+
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        self.words.capacity() * mem::size_of::<u64>()
+    }
+}
+
+fn words_for(len: u32) -> usize {
+    (len as usize).div_ceil(64)
+}
+
+/// Indices of the set bits of one word
+pub(crate) struct Bits {
+    base: u32,
+    word: u64,
+}
+
+impl Bits {
+    /// Iterate over the bits of the word at `index` in a set
+    pub(crate) fn new(index: usize, word: u64) -> Self {
+        Self {
+            base: index as u32 * 64,
+            word,
+        }
+    }
+}
+
+impl Iterator for Bits {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.word == 0 {
+            return None;
+        }
+        let bit = self.word.trailing_zeros();
+        self.word &= self.word - 1;
+        Some(self.base + bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.word.count_ones() as usize;
+        (n, Some(n))
+    }
+}
+
+impl DoubleEndedIterator for Bits {
+    fn next_back(&mut self) -> Option<u32> {
+        if self.word == 0 {
+            return None;
+        }
+        let bit = 63 - self.word.leading_zeros();
+        self.word &= !(1 << bit);
+        Some(self.base + bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascending() {
+        let mut set = BitSet::new_in(Global);
+        set.grow(10);
+        set.grow(200);
+        for i in [3, 64, 130, 199] {
+            set.insert(i);
+        }
+        set.remove(64);
+        assert!(set.iter().eq([3, 130, 199]));
+        assert!(set.iter().rev().eq([199, 130, 3]));
+    }
+}
@@ -0,0 +1,171 @@
+//! LRU tracking through an API that reports every failure as an error
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::{InsertError, Iter, LruSlab, ReserveError, VacantError};
+
+/// An [`LruSlab`] whose every operation returns an error where the equivalent [`LruSlab`]
+/// method would panic
+///
+/// Accessing a vacant slot yields [`VacantError`], and running out of slots or memory yields
+/// [`ReserveError`] rather than panicking or aborting, so callers that must not unwind can handle
+/// each failure explicitly. Operations with no checked equivalent, such as pinning, aren't
+/// exposed; the underlying slab is available through [`slab`](Self::slab) and
+/// [`into_inner`](Self::into_inner).
+///
+/// No operation can panic, as verified for release builds by `tests/no_panic.rs`, unless `T`'s
+/// destructor does. This doesn't hold with the `strict` feature, which panics upon finding
+/// corruption, nor with `trace` or `tracing`, which call out to code that may panic.
+pub struct CheckedLruSlab<T, A: Allocator + Clone = Global> {
+    slab: LruSlab<T, A>,
+}
+
+impl<T> CheckedLruSlab<T> {
+    /// Create an empty [`CheckedLruSlab`]
+    pub const fn new() -> Self {
+        Self {
+            slab: LruSlab::new(),
+        }
+    }
+
+    /// Create a [`CheckedLruSlab`] that can store at least `capacity` elements without
+    /// reallocating
+    pub fn with_capacity(capacity: u32) -> Result<Self, ReserveError> {
+        let mut slab = Self::new();
+        slab.reserve(capacity)?;
+        Ok(slab)
+    }
+}
+
+impl<T, A: Allocator + Clone> CheckedLruSlab<T, A> {
+    /// Create an empty [`CheckedLruSlab`] that allocates from `alloc`
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            slab: LruSlab::new_in(alloc),
+        }
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.slab.is_empty()
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.slab.len()
+    }
+
+    /// Number of elements that can be stored without reallocating
+    pub fn capacity(&self) -> u32 {
+        self.slab.capacity()
+    }
+
+    /// Ensure that at least `additional` more elements can be stored without reallocating
+    pub fn reserve(&mut self, additional: u32) -> Result<(), ReserveError> {
+        self.slab.try_reserve(additional)
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used. On failure, the value is returned
+    /// in the error.
+    pub fn insert(&mut self, value: T) -> Result<u32, InsertError<T>> {
+        self.slab.try_insert(value)
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> Result<T, VacantError> {
+        self.slab.try_remove(slot)
+    }
+
+    /// Get the least recently used slot, if any
+    pub fn lru(&self) -> Option<u32> {
+        self.slab.lru()
+    }
+
+    /// Remove the least recently used element, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        self.slab.pop_lru()
+    }
+
+    /// Whether `slot` is occupied
+    pub fn contains(&self, slot: u32) -> bool {
+        self.slab.contains(slot)
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn get(&self, slot: u32) -> Result<&T, VacantError> {
+        self.slab.get(slot).ok_or(VacantError { slot })
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> Result<&mut T, VacantError> {
+        self.check(slot)?;
+        // Safety: checked above
+        Ok(unsafe { self.slab.get_unchecked_mut(slot) })
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> Result<&mut T, VacantError> {
+        self.check(slot)?;
+        // Safety: checked above
+        Ok(unsafe { self.slab.peek_unchecked_mut(slot) })
+    }
+
+    /// Iterate over the elements from most to least recently used
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.slab.iter()
+    }
+
+    /// The underlying slab
+    pub fn slab(&self) -> &LruSlab<T, A> {
+        &self.slab
+    }
+
+    /// Recover the underlying slab
+    pub fn into_inner(self) -> LruSlab<T, A> {
+        self.slab
+    }
+
+    fn check(&self, slot: u32) -> Result<(), VacantError> {
+        match self.slab.contains(slot) {
+            true => Ok(()),
+            false => Err(VacantError { slot }),
+        }
+    }
+}
+
+impl<T> Default for CheckedLruSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A: Allocator + Clone> From<LruSlab<T, A>> for CheckedLruSlab<T, A> {
+    fn from(slab: LruSlab<T, A>) -> Self {
+        Self { slab }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vacant_slots() {
+        let mut slab = CheckedLruSlab::new();
+        let a = slab.insert('a').unwrap();
+        let b = slab.insert('b').unwrap();
+        assert_eq!(slab.remove(a), Ok('a'));
+        assert_eq!(slab.remove(a), Err(VacantError { slot: a }));
+        assert_eq!(slab.get(a), Err(VacantError { slot: a }));
+        assert_eq!(slab.get_mut(100), Err(VacantError { slot: 100 }));
+        assert_eq!(slab.peek_mut(u32::MAX), Err(VacantError { slot: u32::MAX }));
+        assert_eq!(slab.get_mut(b), Ok(&mut 'b'));
+        assert_eq!(
+            CheckedLruSlab::<u8>::with_capacity(u32::MAX).err(),
+            Some(ReserveError::CapacityOverflow)
+        );
+        slab.slab().check_invariants().unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+//! Approximate LRU via the CLOCK (second chance) algorithm
+
+use alloc::boxed::Box;
+
+use crate::{
+    list::{Link, Links, List},
+    NONE,
+};
+
+/// A fixed-capacity table that approximates LRU eviction with a reference bit per slot
+///
+/// Accessing an entry only sets its reference bit, rather than relinking it at the head of a list
+/// as [`LruSlab`](crate::LruSlab) does. When an entry must be evicted, a hand sweeps the entries in
+/// insertion order, clearing reference bits, and stops at the first entry whose bit was already
+/// clear.
+pub struct ClockSlab<T> {
+    slots: Box<[Slot<T>]>,
+    /// Occupied slots, in the order the hand visits them
+    ring: List,
+    free: List,
+    /// Next slot to be considered for eviction
+    hand: Hand,
+}
+
+impl<T> ClockSlab<T> {
+    /// Create a [`ClockSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        assert!(capacity != u32::MAX, "capacity too large");
+        let mut slots = (0..capacity)
+            .map(|_| Slot {
+                value: None,
+                referenced: false,
+                link: Link::UNLINKED,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..capacity {
+            free.push_back(&mut slots[..], slot);
+        }
+        Self {
+            slots,
+            ring: List::EMPTY,
+            free,
+            hand: Hand::NONE,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.ring.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Insert a value, returning the slot it was stored in and the slot and value of any entry
+    /// evicted to make room
+    ///
+    /// When an entry is evicted, the new value takes over its slot.
+    pub fn insert(&mut self, value: T) -> (u32, Option<(u32, T)>) {
+        if let Some(slot) = self.free.pop_front(&mut self.slots[..]) {
+            // Place the new entry just behind the hand, so it's visited last
+            match self.hand.get() {
+                None => {
+                    self.ring.push_back(&mut self.slots[..], slot);
+                    self.hand = Hand(slot);
+                }
+                Some(hand) => self.ring.insert_before(&mut self.slots[..], hand, slot),
+            }
+            let entry = &mut self.slots[slot as usize];
+            debug_assert!(entry.value.is_none(), "corrupt free list");
+            entry.value = Some(value);
+            entry.referenced = false;
+            return (slot, None);
+        }
+
+        let slot = self.sweep();
+        self.hand.advance(&self.ring, &mut self.slots[..]);
+        let entry = &mut self.slots[slot as usize];
+        entry.referenced = false;
+        let old = entry.value.replace(value).expect("corrupt CLOCK ring");
+        (slot, Some((slot, old)))
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let value = self.slots[slot as usize]
+            .value
+            .take()
+            .expect("removing empty slot");
+        self.hand.step_off(&self.ring, &mut self.slots[..], slot);
+        self.ring.remove(&mut self.slots[..], slot);
+        self.free.push_front(&mut self.slots[..], slot);
+        value
+    }
+
+    /// Mark `slot` as referenced and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &mut self.slots[slot as usize];
+        entry.referenced = true;
+        entry.value.as_mut().unwrap()
+    }
+
+    /// Access `slot` without marking it as referenced
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without marking it as referenced
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Advance the hand to the first slot that hasn't been referenced since the hand last passed
+    fn sweep(&mut self) -> u32 {
+        loop {
+            let slot = self.hand.get().expect("sweeping empty ring");
+            let entry = &mut self.slots[slot as usize];
+            if !entry.referenced {
+                return slot;
+            }
+            entry.referenced = false;
+            self.hand.advance(&self.ring, &mut self.slots[..]);
+        }
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    /// Whether the slot was accessed since the hand last passed it
+    referenced: bool,
+    link: Link,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+/// A cursor sweeping around a [`List`] treated as a ring
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub(crate) struct Hand(pub(crate) u32);
+
+impl Hand {
+    /// A hand on an empty ring
+    pub(crate) const NONE: Self = Self(NONE);
+
+    pub(crate) fn get(self) -> Option<u32> {
+        (self.0 != NONE).then_some(self.0)
+    }
+
+    /// Move to the next slot in `ring`
+    pub(crate) fn advance(&mut self, ring: &List, links: &mut (impl Links + ?Sized)) {
+        self.0 = ring.next_cyclic(links, self.0);
+    }
+
+    /// Move to the previous slot in `ring`
+    pub(crate) fn retreat(&mut self, ring: &List, links: &mut (impl Links + ?Sized)) {
+        self.0 = ring.prev_cyclic(links, self.0);
+    }
+
+    /// Move forward if `slot`, which is about to be removed from `ring`, is under the hand
+    pub(crate) fn step_off(&mut self, ring: &List, links: &mut (impl Links + ?Sized), slot: u32) {
+        if self.0 != slot {
+            return;
+        }
+        if ring.len == 1 {
+            *self = Self::NONE;
+        } else {
+            self.advance(ring, links);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_chance() {
+        let mut cache = ClockSlab::new(3);
+        let (a, _) = cache.insert('a');
+        cache.insert('b');
+        cache.insert('c');
+        cache.get_mut(a);
+        let (d, evicted) = cache.insert('d');
+        assert_eq!(evicted.map(|(_, x)| x), Some('b'));
+        assert_eq!(*cache.peek(d), 'd');
+        // `a`'s reference bit was cleared by the last sweep
+        let (_, evicted) = cache.insert('e');
+        assert_eq!(evicted.map(|(_, x)| x), Some('c'));
+        let (_, evicted) = cache.insert('f');
+        assert_eq!(evicted.map(|(_, x)| x), Some('a'));
+    }
+
+    #[test]
+    fn remove_under_hand() {
+        let mut cache = ClockSlab::new(2);
+        let (a, _) = cache.insert('a');
+        let (b, _) = cache.insert('b');
+        assert_eq!(cache.remove(a), 'a');
+        assert_eq!(cache.remove(b), 'b');
+        assert!(cache.is_empty());
+        let (c, _) = cache.insert('c');
+        cache.insert('d');
+        let (_, evicted) = cache.insert('e');
+        assert_eq!(evicted, Some((c, 'c')));
+    }
+}
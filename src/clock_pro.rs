@@ -0,0 +1,339 @@
+//! The CLOCK-Pro replacement algorithm
+
+use alloc::boxed::Box;
+
+use crate::{
+    clock::Hand,
+    list::{Link, Links, List},
+    tag_index::TagIndex,
+};
+
+/// A fixed-capacity table that evicts according to the CLOCK-Pro algorithm
+///
+/// Like [`ClockSlab`](crate::ClockSlab), accesses only set a reference bit. Entries are
+/// additionally classified as hot or cold, and only cold entries are evicted. An evicted cold entry
+/// remains in the clock as a non-resident ghost for a test period; if its tag is inserted again
+/// before the test period ends, it returns as a hot entry and the cold allocation grows. This
+/// protects the cache from loops slightly larger than its capacity, which defeat plain CLOCK.
+///
+/// Tags are supplied by the caller on insert and should identify the value, e.g. by hashing its
+/// key. Ghosts occupy slots without storing values, so at most `2 * capacity` slots are in use.
+pub struct ClockProSlab<T> {
+    slots: Box<[Slot<T>]>,
+    /// Every hot, cold, and ghost entry, in clock order
+    ring: List,
+    free: List,
+    /// The hot entry with the largest recency, next to be considered for demotion
+    hand_hot: Hand,
+    /// The oldest resident cold entry, next to be considered for eviction
+    hand_cold: Hand,
+    /// The oldest entry that might be in its test period
+    hand_test: Hand,
+    /// Locates ghosts by tag
+    ghosts: TagIndex,
+    capacity: u32,
+    /// Adaptive target number of cold entries
+    cold_target: u32,
+    hot: u32,
+    cold: u32,
+    test: u32,
+}
+
+impl<T> ClockProSlab<T> {
+    /// Create a [`ClockProSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        let len = capacity.checked_mul(2).expect("capacity too large");
+        assert!(len != u32::MAX, "capacity too large");
+        let mut slots = (0..len)
+            .map(|_| Slot {
+                value: None,
+                tag: 0,
+                referenced: false,
+                kind: Kind::Free,
+                link: Link::UNLINKED,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..len {
+            free.push_back(&mut slots[..], slot);
+        }
+        Self {
+            slots,
+            ring: List::EMPTY,
+            free,
+            hand_hot: Hand::NONE,
+            hand_cold: Hand::NONE,
+            hand_test: Hand::NONE,
+            ghosts: TagIndex::new(capacity),
+            capacity,
+            // Initially favor hot entries, as the paper's non-adaptive variant does
+            cold_target: (capacity / 100).max(1),
+            hot: 0,
+            cold: 0,
+            test: 0,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.hot + self.cold
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Insert a value identified by `tag`, returning the slot it was stored in and the slot and
+    /// value of any entry evicted to make room
+    pub fn insert(&mut self, tag: u64, value: T) -> (u32, Option<(u32, T)>) {
+        let kind = match self.ghosts.remove(tag) {
+            Some(ghost) => {
+                // Reused within its test period, so its reuse distance is short: promote it, and
+                // give cold entries more room to prove themselves in future
+                self.cold_target = (self.cold_target + 1).min(self.capacity);
+                self.test -= 1;
+                self.unlink(ghost);
+                Kind::Hot
+            }
+            None => Kind::Cold,
+        };
+
+        let mut evicted = None;
+        while self.hot + self.cold >= self.capacity {
+            self.run_hand_cold(&mut evicted);
+        }
+
+        let slot = self
+            .free
+            .pop_front(&mut self.slots[..])
+            .expect("no free slots");
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        entry.value = Some(value);
+        entry.tag = tag;
+        entry.referenced = false;
+        entry.kind = kind;
+        match kind {
+            Kind::Hot => self.hot += 1,
+            _ => self.cold += 1,
+        }
+        match self.hand_hot.get() {
+            None => {
+                self.ring.push_back(&mut self.slots[..], slot);
+                self.hand_hot = Hand(slot);
+                self.hand_cold = Hand(slot);
+                self.hand_test = Hand(slot);
+            }
+            Some(hand) => {
+                // New entries are the most recent, so they go at the head of the clock, which
+                // immediately follows the hot hand
+                self.ring.insert_after(&mut self.slots[..], hand, slot);
+                if self.hand_cold == self.hand_hot {
+                    self.hand_cold.advance(&self.ring, &mut self.slots[..]);
+                }
+                if self.hand_test == self.hand_hot {
+                    self.hand_test.advance(&self.ring, &mut self.slots[..]);
+                }
+                self.hand_hot.advance(&self.ring, &mut self.slots[..]);
+            }
+        }
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    ///
+    /// No ghost is left behind.
+    pub fn remove(&mut self, slot: u32) -> T {
+        match self.slots[slot as usize].kind {
+            Kind::Hot => self.hot -= 1,
+            Kind::Cold => self.cold -= 1,
+            _ => panic!("removing empty slot"),
+        }
+        let value = self.slots[slot as usize].value.take().unwrap();
+        self.unlink(slot);
+        value
+    }
+
+    /// Mark `slot` as referenced and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &mut self.slots[slot as usize];
+        entry.referenced = true;
+        entry.value.as_mut().unwrap()
+    }
+
+    /// Access `slot` without marking it as referenced
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without marking it as referenced
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Consider the entry under the cold hand for eviction
+    fn run_hand_cold(&mut self, evicted: &mut Option<(u32, T)>) {
+        let slot = self.hand_cold.0;
+        let entry = &mut self.slots[slot as usize];
+        if entry.kind == Kind::Cold {
+            if entry.referenced {
+                entry.referenced = false;
+                entry.kind = Kind::Hot;
+                self.cold -= 1;
+                self.hot += 1;
+            } else {
+                entry.kind = Kind::Test;
+                let value = entry.value.take().unwrap();
+                let tag = entry.tag;
+                self.cold -= 1;
+                self.test += 1;
+                debug_assert!(evicted.is_none(), "evicted multiple entries");
+                *evicted = Some((slot, value));
+                if let Some(stale) = self.ghosts.insert(tag, slot) {
+                    // The same tag was evicted before; only the newest ghost is meaningful
+                    self.test -= 1;
+                    self.unlink(stale);
+                }
+                while self.test > self.capacity {
+                    self.run_hand_test();
+                }
+            }
+        }
+        self.hand_cold.advance(&self.ring, &mut self.slots[..]);
+        while self.capacity - self.cold_target < self.hot {
+            self.run_hand_hot();
+        }
+    }
+
+    /// Consider the entry under the hot hand for demotion
+    fn run_hand_hot(&mut self) {
+        if self.hand_hot == self.hand_test {
+            self.run_hand_test();
+        }
+        let slot = self.hand_hot.0;
+        let entry = &mut self.slots[slot as usize];
+        if entry.kind == Kind::Hot {
+            if entry.referenced {
+                entry.referenced = false;
+            } else {
+                entry.kind = Kind::Cold;
+                self.hot -= 1;
+                self.cold += 1;
+            }
+        }
+        self.hand_hot.advance(&self.ring, &mut self.slots[..]);
+    }
+
+    /// End the test period of the entry under the test hand
+    fn run_hand_test(&mut self) {
+        if self.hand_test == self.hand_cold {
+            // Keep the test hand behind the cold hand. The cold entry we skip over stays resident
+            // until the cold hand comes around again, ensuring each insert evicts at most once.
+            self.hand_cold.advance(&self.ring, &mut self.slots[..]);
+        }
+        let slot = self.hand_test.0;
+        let entry = &self.slots[slot as usize];
+        if entry.kind == Kind::Test {
+            // Not reused within its test period, so cold entries don't need as much room
+            self.ghosts.remove(entry.tag);
+            self.unlink(slot);
+            self.test -= 1;
+            self.cold_target = (self.cold_target - 1).max(1);
+        }
+        self.hand_test.advance(&self.ring, &mut self.slots[..]);
+    }
+
+    /// Remove `slot` from the clock and free it, moving any hands on it back
+    fn unlink(&mut self, slot: u32) {
+        if self.ring.len == 1 {
+            self.hand_hot = Hand::NONE;
+            self.hand_cold = Hand::NONE;
+            self.hand_test = Hand::NONE;
+        } else {
+            for hand in [&mut self.hand_hot, &mut self.hand_cold, &mut self.hand_test] {
+                if hand.0 == slot {
+                    hand.retreat(&self.ring, &mut self.slots[..]);
+                }
+            }
+        }
+        self.ring.remove(&mut self.slots[..], slot);
+        self.slots[slot as usize].kind = Kind::Free;
+        self.free.push_front(&mut self.slots[..], slot);
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    tag: u64,
+    /// Whether the slot was accessed since a hand last passed it
+    referenced: bool,
+    kind: Kind,
+    link: Link,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Kind {
+    Free,
+    /// Resident, with a short reuse distance
+    Hot,
+    /// Resident, with a long or unknown reuse distance
+    Cold,
+    /// Non-resident ghost of a cold entry within its test period
+    Test,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resists_loop() {
+        // Cycling over one more entry than fits evicts every entry under plain CLOCK or LRU
+        let mut cache = ClockProSlab::new(8);
+        let mut slots = [None; 9];
+        let mut hits = 0;
+        for _ in 0..10 {
+            for tag in 0..9 {
+                match slots[tag] {
+                    Some(slot) => {
+                        hits += 1;
+                        cache.get_mut(slot);
+                    }
+                    None => {
+                        let (slot, evicted) = cache.insert(tag as u64, tag);
+                        if let Some((_, old)) = evicted {
+                            slots[old] = None;
+                        }
+                        slots[tag] = Some(slot);
+                    }
+                }
+            }
+        }
+        assert!(hits > 30, "{hits} hits");
+    }
+
+    #[test]
+    fn remove() {
+        let mut cache = ClockProSlab::new(2);
+        let (a, _) = cache.insert(0, 'a');
+        let (b, _) = cache.insert(1, 'b');
+        assert_eq!(cache.remove(a), 'a');
+        assert_eq!(cache.remove(b), 'b');
+        assert!(cache.is_empty());
+        let (c, _) = cache.insert(2, 'c');
+        assert_eq!(*cache.get_mut(c), 'c');
+    }
+}
@@ -0,0 +1,292 @@
+//! Fixed-capacity LRU tracking with 16-bit slot indices
+
+use core::{iter::FusedIterator, mem::MaybeUninit};
+
+/// An [`LruArray`](crate::LruArray) whose slots are identified by `u16`, halving the space spent
+/// on links
+///
+/// Each slot carries 4 bytes of links rather than 8. `N` must be at most 65534.
+pub struct CompactLruArray<T, const N: usize> {
+    /// Initialized where the corresponding link is occupied
+    values: [MaybeUninit<T>; N],
+    /// `prev` is `FREE` for vacant slots below `unused`, whose `next` links the free list
+    links: [Link; N],
+    /// Most recently used
+    head: u16,
+    /// Least recently used
+    tail: u16,
+    len: u16,
+    /// First slot of the free list
+    free: u16,
+    /// Slots at and above this index have never been occupied, and their links are meaningless
+    unused: u16,
+}
+
+impl<T, const N: usize> CompactLruArray<T, N> {
+    /// Create an empty [`CompactLruArray`]
+    pub const fn new() -> Self {
+        assert!(N <= FREE as usize, "capacity too large");
+        Self {
+            values: [const { MaybeUninit::uninit() }; N],
+            links: [Link {
+                prev: NONE,
+                next: NONE,
+            }; N],
+            head: NONE,
+            tail: NONE,
+            len: 0,
+            free: NONE,
+            unused: 0,
+        }
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether every slot is occupied
+    pub fn is_full(&self) -> bool {
+        self.free == NONE && self.unused as usize == N
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u16 {
+        self.len
+    }
+
+    /// Maximum number of elements that can be stored
+    pub fn capacity(&self) -> u16 {
+        N as u16
+    }
+
+    /// Insert a value, returning the slot it was stored in, or the value if the array is full
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&mut self, value: T) -> Result<u16, T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        let slot = if self.free != NONE {
+            let slot = self.free;
+            self.free = self.links[slot as usize].next;
+            slot
+        } else {
+            self.unused += 1;
+            self.unused - 1
+        };
+        self.values[slot as usize].write(value);
+        self.link_at_head(slot);
+        self.len += 1;
+        Ok(slot)
+    }
+
+    /// Get the least recently used slot, if any
+    pub fn lru(&self) -> Option<u16> {
+        (self.tail != NONE).then_some(self.tail)
+    }
+
+    /// Remove the least recently used element, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u16, T)> {
+        let slot = self.lru()?;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u16) -> T {
+        assert!(self.contains(slot), "removing empty slot");
+        self.unlink(slot);
+        self.links[slot as usize] = Link {
+            prev: FREE,
+            next: self.free,
+        };
+        self.free = slot;
+        self.len -= 1;
+        // Safety: the slot was occupied, and is now vacant
+        unsafe { self.values[slot as usize].assume_init_read() }
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u16) -> &mut T {
+        assert!(self.contains(slot), "accessing empty slot");
+        if self.head != slot {
+            self.unlink(slot);
+            self.link_at_head(slot);
+        }
+        // Safety: checked above
+        unsafe { self.values[slot as usize].assume_init_mut() }
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u16) -> Option<&T> {
+        // Safety: occupied slots are initialized
+        self.contains(slot)
+            .then(|| unsafe { self.values[slot as usize].assume_init_ref() })
+    }
+
+    /// Whether `slot` is occupied
+    pub fn contains(&self, slot: u16) -> bool {
+        slot < self.unused && self.links[slot as usize].prev != FREE
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u16) -> &T {
+        self.get(slot).expect("accessing empty slot")
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u16) -> &mut T {
+        assert!(self.contains(slot), "accessing empty slot");
+        // Safety: checked above
+        unsafe { self.values[slot as usize].assume_init_mut() }
+    }
+
+    /// Walk the container from most to least recently used
+    pub fn iter(&self) -> CompactIter<'_, T> {
+        CompactIter {
+            values: &self.values[..],
+            links: &self.links[..],
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+        }
+    }
+
+    fn link_at_head(&mut self, slot: u16) {
+        self.links[slot as usize] = Link {
+            prev: NONE,
+            next: self.head,
+        };
+        if self.head == NONE {
+            self.tail = slot;
+        } else {
+            self.links[self.head as usize].prev = slot;
+        }
+        self.head = slot;
+    }
+
+    fn unlink(&mut self, slot: u16) {
+        let Link { prev, next } = self.links[slot as usize];
+        if prev == NONE {
+            self.head = next;
+        } else {
+            self.links[prev as usize].next = next;
+        }
+        if next == NONE {
+            self.tail = prev;
+        } else {
+            self.links[next as usize].prev = prev;
+        }
+    }
+}
+
+impl<T, const N: usize> Default for CompactLruArray<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for CompactLruArray<T, N> {
+    fn drop(&mut self) {
+        let unused = self.unused as usize;
+        for (value, link) in self.values[..unused].iter_mut().zip(&self.links) {
+            if link.prev != FREE {
+                // Safety: occupied slots are initialized
+                unsafe { value.assume_init_drop() }
+            }
+        }
+    }
+}
+
+/// Iterator over elements of a [`CompactLruArray`], from most to least recently used
+pub struct CompactIter<'a, T> {
+    values: &'a [MaybeUninit<T>],
+    links: &'a [Link],
+    head: u16,
+    tail: u16,
+    len: u16,
+}
+
+impl<'a, T> Iterator for CompactIter<'a, T> {
+    type Item = (u16, &'a T);
+    fn next(&mut self) -> Option<(u16, &'a T)> {
+        if self.len == 0 {
+            return None;
+        }
+        let slot = self.head;
+        self.head = self.links[slot as usize].next;
+        self.len -= 1;
+        // Safety: slots in the LRU list are occupied
+        let value = unsafe { self.values[slot as usize].assume_init_ref() };
+        Some((slot, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len as usize, Some(self.len as usize))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for CompactIter<'a, T> {
+    fn next_back(&mut self) -> Option<(u16, &'a T)> {
+        if self.len == 0 {
+            return None;
+        }
+        let slot = self.tail;
+        self.tail = self.links[slot as usize].prev;
+        self.len -= 1;
+        // Safety: slots in the LRU list are occupied
+        let value = unsafe { self.values[slot as usize].assume_init_ref() };
+        Some((slot, value))
+    }
+}
+
+impl<T> ExactSizeIterator for CompactIter<'_, T> {
+    fn len(&self) -> usize {
+        self.len as usize
+    }
+}
+
+impl<T> FusedIterator for CompactIter<'_, T> {}
+
+/// Neighbors of a slot in the LRU list
+#[derive(Copy, Clone)]
+struct Link {
+    prev: u16,
+    next: u16,
+}
+
+const NONE: u16 = u16::MAX;
+
+/// `Link::prev` of a vacant slot
+const FREE: u16 = u16::MAX - 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_order() {
+        assert_eq!(core::mem::size_of::<Link>(), 4);
+        let mut array = CompactLruArray::<_, 3>::new();
+        let slots = ['a', 'b', 'c'].map(|x| array.insert(x).unwrap());
+        array.get_mut(slots[0]);
+        assert_eq!(array.remove(slots[1]), 'b');
+        let order = array.iter().map(|(s, &x)| (s, x));
+        assert!(order.eq([(slots[0], 'a'), (slots[2], 'c')]));
+        assert_eq!(array.insert('d'), Ok(slots[1]));
+        assert_eq!(array.pop_lru(), Some((slots[2], 'c')));
+    }
+
+    #[test]
+    fn max_capacity() {
+        let mut array = CompactLruArray::<u8, 65534>::new();
+        for i in 0..65534u32 {
+            assert_eq!(array.insert(i as u8), Ok(i as u16));
+        }
+        assert_eq!(array.insert(0), Err(0));
+        assert_eq!(array.lru(), Some(0));
+        assert_eq!(array.remove(65533), 65533u32 as u8);
+        assert_eq!(array.insert(1), Ok(65533));
+        assert_eq!(array.iter().next(), Some((65533, &1)));
+    }
+}
@@ -0,0 +1,85 @@
+//! Interrupt-safe access to an [`LruSlab`] via the `critical-section` crate
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use crate::LruSlab;
+
+/// An [`LruSlab`] that can be shared between interrupt handlers and the main context
+///
+/// Every method runs inside a critical section for its own duration only. Each takes constant time
+/// except [`insert`](Self::insert), which may reallocate if the slab is full; reserve capacity up
+/// front with [`LruSlab::with_capacity`] to keep critical sections short.
+pub struct CsLruSlab<T> {
+    inner: Mutex<RefCell<LruSlab<T>>>,
+}
+
+impl<T> CsLruSlab<T> {
+    /// Wrap `slab` for interrupt-safe access
+    pub fn new(slab: LruSlab<T>) -> Self {
+        Self {
+            inner: Mutex::new(RefCell::new(slab)),
+        }
+    }
+
+    /// Run `f` with exclusive access to the slab inside a critical section
+    ///
+    /// Interrupts are held off until `f` returns, so keep it short.
+    pub fn with<R>(&self, f: impl FnOnce(&mut LruSlab<T>) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.inner.borrow_ref_mut(cs)))
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.with(|slab| slab.len())
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.with(|slab| slab.is_empty())
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&self, value: T) -> u32 {
+        self.with(|slab| slab.insert(value))
+    }
+
+    /// Mark `slot` as the most recently used and access it with `f`, if it's occupied
+    pub fn get_mut<R>(&self, slot: u32, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.with(|slab| slab.contains(slot).then(|| f(slab.get_mut(slot))))
+    }
+
+    /// Remove the element stored in `slot`, returning it, if any
+    pub fn remove(&self, slot: u32) -> Option<T> {
+        self.with(|slab| slab.contains(slot).then(|| slab.remove(slot)))
+    }
+
+    /// Remove the least recently used element that isn't pinned, returning its slot and value
+    pub fn pop_lru(&self) -> Option<(u32, T)> {
+        self.with(|slab| slab.pop_lru())
+    }
+
+    /// Unwrap the slab
+    pub fn into_inner(self) -> LruSlab<T> {
+        self.inner.into_inner().into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared() {
+        let slab = CsLruSlab::new(LruSlab::with_capacity(2));
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        assert_eq!(slab.get_mut(a, |x| *x), Some('a'));
+        assert_eq!(slab.pop_lru(), Some((b, 'b')));
+        assert_eq!(slab.remove(b), None);
+        assert_eq!(slab.len(), 1);
+    }
+}
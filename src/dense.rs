@@ -0,0 +1,149 @@
+//! LRU tracking with values packed contiguously
+
+use alloc::vec::Vec;
+
+use crate::LruSlab;
+
+/// A random-access table that maintains an LRU list, storing its values contiguously
+///
+/// Values are kept packed at the front of a single array, exposed by [`values`](Self::values),
+/// so they can be copied out in one operation. Removal moves the last value into the gap, so the
+/// position of a value in that array may change, but its slot never does. Recency is tracked by
+/// an [`LruSlab`] of positions, costing one extra indirection per lookup.
+pub struct DenseLruSlab<T> {
+    /// Position in `values` of each slot's value
+    positions: LruSlab<u32>,
+    values: Vec<T>,
+    /// Slot of each value
+    slots: Vec<u32>,
+}
+
+impl<T> DenseLruSlab<T> {
+    /// Create an empty [`DenseLruSlab`]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a [`DenseLruSlab`] that can store at least `capacity` elements without reallocating
+    pub fn with_capacity(capacity: u32) -> Self {
+        Self {
+            positions: LruSlab::with_capacity(capacity),
+            values: Vec::with_capacity(capacity as usize),
+            slots: Vec::with_capacity(capacity as usize),
+        }
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.positions.len()
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&mut self, value: T) -> u32 {
+        let slot = self.positions.insert(self.values.len() as u32);
+        self.values.push(value);
+        self.slots.push(slot);
+        slot
+    }
+
+    /// Get the least recently used slot, if any
+    pub fn lru(&self) -> Option<u32> {
+        self.positions.lru()
+    }
+
+    /// Remove the least recently used element, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        let slot = self.lru()?;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let position = self.positions.remove(slot);
+        let value = self.values.swap_remove(position as usize);
+        self.slots.swap_remove(position as usize);
+        if let Some(&moved) = self.slots.get(position as usize) {
+            *self.positions.peek_mut(moved) = position;
+        }
+        value
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let position = *self.positions.get_mut(slot);
+        &mut self.values[position as usize]
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u32) -> Option<&T> {
+        let &position = self.positions.get(slot)?;
+        Some(&self.values[position as usize])
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        let position = *self.positions.peek(slot);
+        &mut self.values[position as usize]
+    }
+
+    /// Position of the value of `slot` in [`values`](Self::values), if it's occupied
+    pub fn position_of(&self, slot: u32) -> Option<u32> {
+        self.positions.get(slot).copied()
+    }
+
+    /// Every stored value, packed contiguously in no particular order
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Unique access to every stored value, packed contiguously in no particular order
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// The slot of each value in [`values`](Self::values), at the same position
+    pub fn slots(&self) -> &[u32] {
+        &self.slots
+    }
+
+    /// Iterate over the elements from most to least recently used
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (u32, &T)> + '_ {
+        self.positions
+            .iter()
+            .map(|(slot, &position)| (slot, &self.values[position as usize]))
+    }
+}
+
+impl<T> Default for DenseLruSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stays_packed() {
+        let mut slab = DenseLruSlab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        let c = slab.insert('c');
+        slab.get_mut(a);
+        assert_eq!(slab.remove(b), 'b');
+        assert_eq!(slab.values(), ['a', 'c']);
+        assert_eq!(slab.slots(), [a, c]);
+        assert_eq!(slab.remove(a), 'a');
+        assert_eq!(slab.values(), ['c']);
+        assert_eq!(slab.position_of(c), Some(0));
+        assert!(slab.iter().eq([(c, &'c')]));
+    }
+}
@@ -0,0 +1,132 @@
+//! Bloom filter admission for rejecting one-hit wonders
+
+use alloc::boxed::Box;
+
+use allocator_api2::alloc::Allocator;
+
+use crate::LruSlab;
+
+/// A small bloom filter that admits a tag only once it has been seen before
+///
+/// Insert through [`LruSlab::insert_admitted`], or consult [`admit`](Self::admit) by hand, so
+/// that entries which are only ever requested once are rejected rather than displacing more
+/// useful ones:
+///
+/// ```
+/// # use lru_slab::{Doorkeeper, LruSlab};
+/// let mut doorkeeper = Doorkeeper::new(100);
+/// let mut cache = LruSlab::<u64>::new();
+/// for tag in [1, 2, 1] {
+///     let _ = cache.insert_admitted(tag, tag, &mut doorkeeper);
+/// }
+/// assert_eq!(cache.len(), 1);
+/// ```
+///
+/// The filter clears itself after recording as many tags as it was sized for, so it reflects
+/// recent requests and its false positive rate stays low.
+#[derive(Clone)]
+pub struct Doorkeeper {
+    bits: Box<[u64]>,
+    additions: u32,
+    /// Number of additions after which the filter is cleared
+    sample_size: u32,
+}
+
+impl Doorkeeper {
+    /// Create a [`Doorkeeper`] that remembers about `capacity` distinct tags between resets
+    pub fn new(capacity: u32) -> Self {
+        // About eight bits per tag, giving a false positive rate of a few percent
+        let words = (capacity as usize).next_power_of_two().div_ceil(8).max(1);
+        Self {
+            bits: (0..words).map(|_| 0).collect(),
+            additions: 0,
+            sample_size: capacity.max(1),
+        }
+    }
+
+    /// Whether `tag` may have been recorded since the last reset
+    pub fn contains(&self, tag: u64) -> bool {
+        (0..HASHES).all(|i| {
+            let (word, bit) = self.bit(tag, i);
+            self.bits[word] & bit != 0
+        })
+    }
+
+    /// Record `tag`, returning whether it was already present
+    ///
+    /// A `false` result indicates a first sighting, which should not be admitted.
+    pub fn admit(&mut self, tag: u64) -> bool {
+        if self.contains(tag) {
+            return true;
+        }
+        for i in 0..HASHES {
+            let (word, bit) = self.bit(tag, i);
+            self.bits[word] |= bit;
+        }
+        self.additions += 1;
+        if self.additions == self.sample_size {
+            self.clear();
+        }
+        false
+    }
+
+    /// Forget all recorded tags
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.additions = 0;
+    }
+
+    /// Locate the `i`th bit for `tag`, by double hashing
+    fn bit(&self, tag: u64, i: u32) -> (usize, u64) {
+        let hash = tag.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let (a, b) = (hash >> 32, (hash & 0xFFFF_FFFF) | 1);
+        let index = a.wrapping_add(u64::from(i).wrapping_mul(b)) as usize % (self.bits.len() * 64);
+        (index / 64, 1 << (index % 64))
+    }
+}
+
+impl<T, A: Allocator + Clone> LruSlab<T, A> {
+    /// Insert a value identified by `tag` as if by [`insert`](Self::insert), unless `doorkeeper`
+    /// hasn't seen `tag` before, in which case the value is returned
+    pub fn insert_admitted(
+        &mut self,
+        tag: u64,
+        value: T,
+        doorkeeper: &mut Doorkeeper,
+    ) -> Result<u32, T> {
+        match doorkeeper.admit(tag) {
+            true => Ok(self.insert(value)),
+            false => Err(value),
+        }
+    }
+}
+
+/// Number of bits set per tag
+const HASHES: u32 = 4;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admits_second_sighting() {
+        let mut doorkeeper = Doorkeeper::new(4);
+        assert!(!doorkeeper.admit(7));
+        assert!(doorkeeper.admit(7));
+        for tag in 0..3 {
+            doorkeeper.admit(100 + tag);
+        }
+        // Reached capacity, so the filter was reset
+        assert!(!doorkeeper.contains(7));
+    }
+
+    #[test]
+    fn insert_admitted() {
+        let mut doorkeeper = Doorkeeper::new(100);
+        let mut slab = LruSlab::new();
+        assert_eq!(slab.insert_admitted(1, 'a', &mut doorkeeper), Err('a'));
+        assert_eq!(slab.insert_admitted(2, 'b', &mut doorkeeper), Err('b'));
+        let a = slab.insert_admitted(1, 'a', &mut doorkeeper).unwrap();
+        assert!(slab.iter().eq([(a, &'a')]));
+    }
+}
@@ -0,0 +1,199 @@
+//! A stable binary encoding of an [`LruSlab`]
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use allocator_api2::alloc::Allocator;
+
+use crate::{InvariantError, LruSlab, RawParts, RawSlot};
+
+impl<T, A: Allocator + Clone> LruSlab<T, A> {
+    /// Append a versioned, checksummed encoding of the slab to `out`, using `encode` to append
+    /// the encoding of each element
+    ///
+    /// Accesses recorded by [`record_access`](Self::record_access) but not yet flushed are not
+    /// included. All integers are little-endian. The checksum detects accidental corruption, not
+    /// tampering.
+    pub fn to_bytes(&self, out: &mut Vec<u8>, mut encode: impl FnMut(&T, &mut Vec<u8>)) {
+        let start = out.len();
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        for x in [self.capacity(), self.head, self.tail, self.free, self.len] {
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        for slot in 0..self.capacity() {
+            let link = self.links[slot as usize];
+            for x in [link.prev, link.next, self.meta[slot as usize].pins] {
+                out.extend_from_slice(&x.to_le_bytes());
+            }
+            match self.get(slot) {
+                None => out.push(0),
+                Some(value) => {
+                    out.push(1);
+                    let len_at = out.len();
+                    out.extend_from_slice(&[0; 4]);
+                    encode(value, out);
+                    let len = u32::try_from(out.len() - len_at - 4).expect("element too large");
+                    out[len_at..len_at + 4].copy_from_slice(&len.to_le_bytes());
+                }
+            }
+        }
+        let checksum = fnv1a(&out[start..]);
+        out.extend_from_slice(&checksum.to_le_bytes());
+    }
+}
+
+impl<T> LruSlab<T> {
+    /// Reconstruct an [`LruSlab`] from the output of [`to_bytes`](Self::to_bytes), using
+    /// `decode` to decode each element
+    pub fn from_bytes(
+        bytes: &[u8],
+        mut decode: impl FnMut(&[u8]) -> Option<T>,
+    ) -> Result<Self, DecodeError> {
+        let body_len = bytes.len().checked_sub(8).ok_or(DecodeError::Truncated)?;
+        let (body, checksum) = bytes.split_at(body_len);
+        let mut r = Reader(body);
+        if r.take(4)? != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let version = u16::from_le_bytes(r.array()?);
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        if fnv1a(body).to_le_bytes() != checksum {
+            return Err(DecodeError::ChecksumMismatch);
+        }
+        let [capacity, head, tail, free, len] = [(); 5].map(|()| r.u32());
+        let capacity = capacity?;
+        // Every slot takes at least 13 bytes, so this bounds the allocation
+        if capacity as usize > r.0.len() / 13 {
+            return Err(DecodeError::Truncated);
+        }
+        let mut slots = Vec::with_capacity(capacity as usize);
+        for slot in 0..capacity {
+            let [prev, next, pins] = [(); 3].map(|()| r.u32());
+            let value = match r.take(1)?[0] {
+                0 => None,
+                1 => {
+                    let len = r.u32()?;
+                    let value = decode(r.take(len as usize)?);
+                    Some(value.ok_or(DecodeError::InvalidElement(slot))?)
+                }
+                _ => return Err(DecodeError::InvalidElement(slot)),
+            };
+            slots.push(RawSlot {
+                value,
+                prev: prev?,
+                next: next?,
+                pins: pins?,
+            });
+        }
+        if !r.0.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        let parts = RawParts {
+            slots,
+            head: head?,
+            tail: tail?,
+            free: free?,
+            len: len?,
+        };
+        Self::from_raw_parts(parts).map_err(DecodeError::Inconsistent)
+    }
+}
+
+/// Reasons that [`LruSlab::from_bytes`] can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeError {
+    /// The input ended early
+    Truncated,
+    /// The input doesn't begin with the expected signature
+    BadMagic,
+    /// The input was encoded in an unknown version of the format
+    UnsupportedVersion(u16),
+    /// The input was corrupted
+    ChecksumMismatch,
+    /// The element in the given slot couldn't be decoded
+    InvalidElement(u32),
+    /// Unexpected data follows the encoded slab
+    TrailingBytes,
+    /// The encoded slab's bookkeeping is inconsistent
+    Inconsistent(InvariantError),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated input"),
+            Self::BadMagic => f.write_str("not an encoded LruSlab"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported format version {v}"),
+            Self::ChecksumMismatch => f.write_str("checksum mismatch"),
+            Self::InvalidElement(slot) => write!(f, "invalid element in slot {slot}"),
+            Self::TrailingBytes => f.write_str("trailing bytes"),
+            Self::Inconsistent(e) => write!(f, "inconsistent slab: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Inconsistent(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Consumes the front of a byte slice
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < n {
+            return Err(DecodeError::Truncated);
+        }
+        let (front, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(front)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        self.array().map(u32::from_le_bytes)
+    }
+}
+
+/// 64-bit FNV-1a hash
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+const MAGIC: [u8; 4] = *b"LRUS";
+const VERSION: u16 = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut slab = (0..5u16).collect::<LruSlab<_>>();
+        slab.remove(1);
+        slab.get_mut(3);
+        let mut bytes = Vec::new();
+        slab.to_bytes(&mut bytes, |x, out| out.extend_from_slice(&x.to_le_bytes()));
+        let decode = |x: &[u8]| Some(u16::from_le_bytes(x.try_into().ok()?));
+        let copy = LruSlab::from_bytes(&bytes, decode).unwrap();
+        assert!(copy.iter().eq(slab.iter()));
+
+        bytes[20] ^= 1;
+        let err = LruSlab::from_bytes(&bytes, decode).err();
+        assert_eq!(err, Some(DecodeError::ChecksumMismatch));
+    }
+}
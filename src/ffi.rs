@@ -0,0 +1,167 @@
+//! C bindings for an [`LruSlab`] of untyped pointers
+//!
+//! Slabs are created by [`lru_slab_new`] and must be freed by [`lru_slab_free`]. Every other
+//! function requires a live slab, and no slab may be used from several threads at once. Stored
+//! pointers are never dereferenced, so ownership of whatever they point to remains with the
+//! caller. Functions that look up a slot fail by returning `false` when it's vacant.
+
+use alloc::boxed::Box;
+use core::ffi::c_void;
+
+use crate::LruSlab;
+
+/// Opaque handle to an [`LruSlab`] of pointers
+pub struct LruSlabC(LruSlab<*mut c_void>);
+
+/// Returned by [`lru_slab_lru`] when no slot is occupied
+pub const LRU_SLAB_NONE: u32 = u32::MAX;
+
+/// Create an empty slab
+#[no_mangle]
+pub extern "C" fn lru_slab_new() -> *mut LruSlabC {
+    Box::into_raw(Box::new(LruSlabC(LruSlab::new())))
+}
+
+/// Free a slab created by [`lru_slab_new`], which may be null
+///
+/// # Safety
+///
+/// `slab` must be null or live, and is dangling afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_free(slab: *mut LruSlabC) {
+    if !slab.is_null() {
+        drop(Box::from_raw(slab));
+    }
+}
+
+/// Number of occupied slots
+///
+/// # Safety
+///
+/// `slab` must be live.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_len(slab: *const LruSlabC) -> u32 {
+    (*slab).0.len()
+}
+
+/// Store `value`, returning the slot it was stored in, which becomes the most recently used
+///
+/// # Safety
+///
+/// `slab` must be live.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_insert(slab: *mut LruSlabC, value: *mut c_void) -> u32 {
+    (*slab).0.insert(value)
+}
+
+/// Mark `slot` as the most recently used and write its value to `out`
+///
+/// # Safety
+///
+/// `slab` must be live, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_get(
+    slab: *mut LruSlabC,
+    slot: u32,
+    out: *mut *mut c_void,
+) -> bool {
+    let slab = &mut (*slab).0;
+    if !slab.contains(slot) {
+        return false;
+    }
+    *out = *slab.get_mut(slot);
+    true
+}
+
+/// Write the value of `slot` to `out` without marking it as most recently used
+///
+/// # Safety
+///
+/// `slab` must be live, and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_peek(
+    slab: *const LruSlabC,
+    slot: u32,
+    out: *mut *mut c_void,
+) -> bool {
+    match (*slab).0.get(slot) {
+        Some(&value) => {
+            *out = value;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Mark `slot` as the most recently used
+///
+/// # Safety
+///
+/// `slab` must be live.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_touch(slab: *mut LruSlabC, slot: u32) -> bool {
+    let slab = &mut (*slab).0;
+    if !slab.contains(slot) {
+        return false;
+    }
+    slab.get_mut(slot);
+    true
+}
+
+/// The least recently used slot that isn't pinned, or [`LRU_SLAB_NONE`]
+///
+/// # Safety
+///
+/// `slab` must be live.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_lru(slab: *const LruSlabC) -> u32 {
+    (*slab).0.lru().unwrap_or(LRU_SLAB_NONE)
+}
+
+/// Vacate `slot`, writing its value to `out` if `out` isn't null
+///
+/// # Safety
+///
+/// `slab` must be live, and `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn lru_slab_remove(
+    slab: *mut LruSlabC,
+    slot: u32,
+    out: *mut *mut c_void,
+) -> bool {
+    let slab = &mut (*slab).0;
+    if !slab.contains(slot) {
+        return false;
+    }
+    let value = slab.remove(slot);
+    if !out.is_null() {
+        *out = value;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ptr;
+
+    use super::*;
+
+    #[test]
+    fn lifecycle() {
+        let mut x = 0u8;
+        let p = &mut x as *mut u8 as *mut c_void;
+        unsafe {
+            let slab = lru_slab_new();
+            let a = lru_slab_insert(slab, p);
+            let b = lru_slab_insert(slab, ptr::null_mut());
+            assert!(lru_slab_touch(slab, a));
+            assert_eq!(lru_slab_lru(slab), b);
+            let mut out = ptr::null_mut();
+            assert!(lru_slab_remove(slab, a, &mut out));
+            assert_eq!(out, p);
+            assert!(!lru_slab_get(slab, a, &mut out));
+            assert_eq!(lru_slab_len(slab), 1);
+            lru_slab_free(slab);
+        }
+    }
+}
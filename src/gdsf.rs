@@ -0,0 +1,264 @@
+//! Cost-aware GreedyDual-Size-Frequency eviction
+
+use alloc::boxed::Box;
+
+use crate::NONE;
+
+/// A fixed-capacity table that evicts according to the GreedyDual-Size-Frequency policy
+///
+/// Each entry has a priority of `inflation + frequency * cost / size`, where `cost` and `size` are
+/// supplied by the caller on insert and `frequency` counts uses. The entry with the lowest priority
+/// is evicted, and its priority becomes the new inflation value, so that entries which are no
+/// longer used eventually age out even if they were once valuable.
+///
+/// Entries are ordered by a binary heap, so insertion, use, and eviction are logarithmic.
+pub struct GdsfSlab<T> {
+    slots: Box<[Slot<T>]>,
+    /// Occupied slots, ordered as a min-heap by priority
+    heap: Box<[u32]>,
+    len: u32,
+    /// First unused slot
+    free: u32,
+    /// Priority of the most recently evicted entry
+    inflation: f64,
+}
+
+impl<T> GdsfSlab<T> {
+    /// Create a [`GdsfSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        assert!(capacity != u32::MAX, "capacity too large");
+        Self {
+            slots: (0..capacity)
+                .map(|n| Slot {
+                    value: None,
+                    cost: 0.0,
+                    size: 0,
+                    frequency: 0,
+                    priority: 0.0,
+                    position: if n + 1 == capacity { NONE } else { n + 1 },
+                })
+                .collect(),
+            heap: (0..capacity).map(|_| NONE).collect(),
+            len: 0,
+            free: 0,
+            inflation: 0.0,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Insert a value that costs `cost` to reproduce and occupies `size` units of some external
+    /// resource, returning the slot it was stored in and the slot and value of any entry evicted to
+    /// make room
+    ///
+    /// When an entry is evicted, the new value takes over its slot. Panics unless `cost` is finite
+    /// and non-negative, so that priorities never fall.
+    pub fn insert(&mut self, value: T, cost: f64, size: u64) -> (u32, Option<(u32, T)>) {
+        assert!(
+            cost.is_finite() && cost >= 0.0,
+            "cost must be finite and non-negative"
+        );
+        let evicted = match self.free {
+            NONE => self.evict(),
+            _ => None,
+        };
+        let slot = self.free;
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        self.free = entry.position;
+        entry.value = Some(value);
+        entry.cost = cost;
+        entry.size = size;
+        entry.frequency = 1;
+        entry.priority = priority(self.inflation, entry);
+        entry.position = self.len;
+        self.heap[self.len as usize] = slot;
+        self.len += 1;
+        self.sift_up(self.len - 1);
+        (slot, evicted)
+    }
+
+    /// The slot that will be evicted next, if any
+    pub fn victim(&self) -> Option<u32> {
+        (self.len != 0).then(|| self.heap[0])
+    }
+
+    /// Remove the entry with the lowest priority, raising the inflation value to match
+    ///
+    /// Useful for enforcing a budget of some external resource measured by entry sizes.
+    pub fn evict(&mut self) -> Option<(u32, T)> {
+        let slot = self.victim()?;
+        self.inflation = self.slots[slot as usize].priority;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    ///
+    /// Unlike [`evict`](Self::evict), the inflation value is unaffected.
+    pub fn remove(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        let value = entry.value.take().expect("removing empty slot");
+        let position = entry.position;
+        entry.position = self.free;
+        self.free = slot;
+        self.len -= 1;
+        if position != self.len {
+            let last = self.heap[self.len as usize];
+            self.heap[position as usize] = last;
+            self.slots[last as usize].position = position;
+            self.sift_up(position);
+            self.sift_down(self.slots[last as usize].position);
+        }
+        value
+    }
+
+    /// Record a use of `slot`, raising its priority, and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &mut self.slots[slot as usize];
+        assert!(entry.value.is_some(), "accessing empty slot");
+        entry.frequency = entry.frequency.saturating_add(1);
+        entry.priority = priority(self.inflation, entry);
+        // Costs are non-negative and inflation never falls, so the priority can only have risen
+        let position = entry.position;
+        self.sift_down(position);
+        self.peek_mut(slot)
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    fn sift_up(&mut self, mut position: u32) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if !self.less(position, parent) {
+                break;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position: u32) {
+        loop {
+            let left = 2 * position + 1;
+            if left >= self.len {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.len && self.less(right, left) {
+                right
+            } else {
+                left
+            };
+            if !self.less(child, position) {
+                break;
+            }
+            self.swap(position, child);
+            position = child;
+        }
+    }
+
+    /// Whether the entry at heap position `a` has a lower priority than that at `b`
+    fn less(&self, a: u32, b: u32) -> bool {
+        let a = self.slots[self.heap[a as usize] as usize].priority;
+        let b = self.slots[self.heap[b as usize] as usize].priority;
+        a.total_cmp(&b).is_lt()
+    }
+
+    fn swap(&mut self, a: u32, b: u32) {
+        self.heap.swap(a as usize, b as usize);
+        let slot_a = self.heap[a as usize];
+        let slot_b = self.heap[b as usize];
+        self.slots[slot_a as usize].position = a;
+        self.slots[slot_b as usize].position = b;
+    }
+}
+
+fn priority<T>(inflation: f64, entry: &Slot<T>) -> f64 {
+    inflation + entry.frequency as f64 * entry.cost / entry.size.max(1) as f64
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    cost: f64,
+    size: u64,
+    frequency: u32,
+    priority: f64,
+    /// Index in the heap, or the next slot in the free list when vacant
+    position: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_cheapest() {
+        let mut cache = GdsfSlab::new(3);
+        cache.insert('a', 10.0, 1);
+        let (b, _) = cache.insert('b', 1.0, 1);
+        cache.insert('c', 10.0, 10);
+        assert_eq!(cache.victim(), Some(b));
+        let (_, evicted) = cache.insert('d', 5.0, 1);
+        assert_eq!(evicted, Some((b, 'b')));
+        // 'c' was inserted before inflation rose, so it's now the lowest
+        let (_, evicted) = cache.insert('e', 5.0, 1);
+        assert_eq!(evicted.map(|(_, x)| x), Some('c'));
+    }
+
+    #[test]
+    fn inflation_ages_out_stale_entries() {
+        let mut cache = GdsfSlab::new(2);
+        let (a, _) = cache.insert('a', 4.5, 1);
+        for _ in 0..5 {
+            let (_, evicted) = cache.insert('x', 1.0, 1);
+            assert_ne!(evicted.map(|(_, x)| x), Some('a'));
+        }
+        // Each eviction raised the inflation value, and new entries now outrank 'a'
+        let (_, evicted) = cache.insert('y', 1.0, 1);
+        assert_eq!(evicted, Some((a, 'a')));
+    }
+
+    #[test]
+    fn mixed_costs() {
+        let mut cache = GdsfSlab::new(8);
+        let slots =
+            [3.0, 0.0, 7.5, 1e-3, 2.0, 0.0, 1e9, 4.0].map(|cost| cache.insert((), cost, 3).0);
+        for &slot in slots.iter().step_by(3) {
+            cache.get_mut(slot);
+        }
+        let mut last = f64::NEG_INFINITY;
+        while let Some((slot, ())) = cache.evict() {
+            // Evictions come in priority order only if the heap stayed ordered
+            assert!(cache.inflation >= last, "slot {slot} evicted out of order");
+            last = cache.inflation;
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn nan_cost() {
+        GdsfSlab::new(1).insert((), f64::NAN, 1);
+    }
+}
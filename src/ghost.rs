@@ -0,0 +1,157 @@
+//! Bounded memory of recently evicted entries
+
+use alloc::boxed::Box;
+use core::fmt;
+
+use crate::{
+    list::{Link, Links, List},
+    tag_index::TagIndex,
+    NONE,
+};
+
+/// Remembers the tags of the most recently evicted entries, without their values
+///
+/// Record an entry's tag when evicting it, e.g. after removing the slot returned by
+/// [`LruSlab::lru`](crate::LruSlab::lru), and check for it when inserting. A tag that's still
+/// remembered is a "ghost hit": the entry would have been retained by a slightly larger cache,
+/// which adaptive policies use to rebalance. Tags may be hashes of keys, or simply slot indices.
+///
+/// Once full, recording a tag forgets the least recently recorded one. All operations take
+/// constant time.
+#[derive(Clone)]
+pub struct GhostList {
+    ghosts: Box<[Ghost]>,
+    /// Recorded ghosts, most recent first
+    recent: List,
+    free: List,
+    index: TagIndex,
+}
+
+impl GhostList {
+    /// Create a [`GhostList`] that remembers up to `capacity` tags
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != u32::MAX, "capacity too large");
+        let mut ghosts = (0..capacity)
+            .map(|_| Ghost {
+                tag: 0,
+                link: Link::UNLINKED,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..capacity {
+            free.push_back(&mut ghosts[..], slot);
+        }
+        Self {
+            ghosts,
+            recent: List::EMPTY,
+            free,
+            index: TagIndex::new(capacity),
+        }
+    }
+
+    /// Whether no tags are remembered
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
+    }
+
+    /// Number of tags remembered
+    pub fn len(&self) -> u32 {
+        self.recent.len
+    }
+
+    /// Maximum number of tags remembered
+    pub fn capacity(&self) -> u32 {
+        self.ghosts.len() as u32
+    }
+
+    /// Remember that the entry identified by `tag` was evicted
+    ///
+    /// Recording a tag that's already remembered makes it the most recent.
+    pub fn record(&mut self, tag: u64) {
+        if !self.remove(tag) && self.free.is_empty() {
+            let Some(oldest) = self.recent.pop_back(&mut self.ghosts[..]) else {
+                // Zero capacity
+                return;
+            };
+            self.index.remove(self.ghosts[oldest as usize].tag);
+            self.free.push_front(&mut self.ghosts[..], oldest);
+        }
+        let slot = self.free.pop_front(&mut self.ghosts[..]).unwrap();
+        self.ghosts[slot as usize].tag = tag;
+        self.recent.push_front(&mut self.ghosts[..], slot);
+        self.index.insert(tag, slot);
+    }
+
+    /// Forget `tag`, returning whether it was remembered, i.e. whether re-inserting it is a ghost
+    /// hit
+    pub fn remove(&mut self, tag: u64) -> bool {
+        let Some(slot) = self.index.remove(tag) else {
+            return false;
+        };
+        self.recent.remove(&mut self.ghosts[..], slot);
+        self.free.push_front(&mut self.ghosts[..], slot);
+        true
+    }
+
+    /// Forget all tags
+    pub fn clear(&mut self) {
+        while let Some(slot) = self.recent.pop_front(&mut self.ghosts[..]) {
+            self.index.remove(self.ghosts[slot as usize].tag);
+            self.free.push_front(&mut self.ghosts[..], slot);
+        }
+    }
+}
+
+impl fmt::Debug for GhostList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut slot = self.recent.head;
+        while slot != NONE {
+            let ghost = &self.ghosts[slot as usize];
+            list.entry(&ghost.tag);
+            slot = ghost.link.next;
+        }
+        list.finish()
+    }
+}
+
+#[derive(Clone)]
+struct Ghost {
+    tag: u64,
+    link: Link,
+}
+
+impl Links for [Ghost] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_oldest() {
+        let mut ghosts = GhostList::new(2);
+        ghosts.record(1);
+        ghosts.record(2);
+        // Refresh 1 so that 2 is forgotten first
+        ghosts.record(1);
+        ghosts.record(3);
+        assert_eq!(ghosts.len(), 2);
+        assert!(!ghosts.remove(2));
+        assert!(ghosts.remove(1));
+        assert!(!ghosts.remove(1));
+        assert!(ghosts.remove(3));
+        assert!(ghosts.is_empty());
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut ghosts = GhostList::new(0);
+        ghosts.record(1);
+        assert!(ghosts.is_empty());
+        assert!(!ghosts.remove(1));
+    }
+}
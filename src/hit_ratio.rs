@@ -0,0 +1,117 @@
+//! Estimating how the hit ratio of an LRU cache varies with its capacity
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::RankedLruSlab;
+
+/// Estimates the hit ratio an LRU cache of each of several capacities would achieve on a stream
+/// of accesses
+///
+/// Follows SHARDS: only keys whose hash falls in a fixed fraction of the hash space are tracked,
+/// and their reuse distances are scaled up by the inverse of that fraction. Lower sample rates
+/// give noisier estimates in proportionally less time and memory, which is bounded by the largest
+/// capacity times the sample rate. Keys are mixed before sampling, so they needn't be hashed
+/// well, but distinct keys must be distinct `u64`s.
+pub struct HitRatioEstimator {
+    /// Sampled keys, by recency
+    recency: RankedLruSlab<u64>,
+    /// Slot in `recency` of each sampled key
+    slots: BTreeMap<u64, u32>,
+    /// Keys whose mixed hash is at most this are sampled
+    threshold: u64,
+    rate: f64,
+    /// Number of sampled keys needed to cover the largest capacity
+    limit: u32,
+    capacities: Vec<u32>,
+    /// Sampled accesses that would hit in a cache of each capacity
+    hits: Vec<u64>,
+    /// Sampled accesses
+    accesses: u64,
+}
+
+impl HitRatioEstimator {
+    /// Create an estimator for caches of each of `capacities`, sampling about `rate` of all
+    /// keys, clamped to `0.0..=1.0`
+    pub fn new(capacities: &[u32], rate: f64) -> Self {
+        let rate = rate.clamp(0.0, 1.0);
+        let max = f64::from(capacities.iter().copied().max().unwrap_or(0)) * rate;
+        Self {
+            recency: RankedLruSlab::new(),
+            slots: BTreeMap::new(),
+            threshold: (rate * 18_446_744_073_709_551_616.0) as u64,
+            rate,
+            limit: max as u32 + u32::from((max as u32 as f64) < max),
+            capacities: capacities.to_vec(),
+            hits: alloc::vec![0; capacities.len()],
+            accesses: 0,
+        }
+    }
+
+    /// Record an access to `key`
+    pub fn access(&mut self, key: u64) {
+        if mix(key) > self.threshold {
+            return;
+        }
+        self.accesses += 1;
+        if let Some(&slot) = self.slots.get(&key) {
+            let distance = f64::from(self.recency.rank_of(slot).unwrap());
+            for (&capacity, hits) in self.capacities.iter().zip(&mut self.hits) {
+                if distance < f64::from(capacity) * self.rate {
+                    *hits += 1;
+                }
+            }
+            self.recency.get_mut(slot);
+            return;
+        }
+        self.slots.insert(key, self.recency.insert(key));
+        // Keys beyond the largest capacity can't hit, so needn't be tracked
+        while self.recency.len() > self.limit {
+            let (_, key) = self.recency.pop_lru().unwrap();
+            self.slots.remove(&key);
+        }
+    }
+
+    /// The estimated hit ratio for each capacity, in the order they were supplied
+    ///
+    /// Every ratio is zero until an access has been sampled.
+    pub fn hit_ratios(&self) -> impl Iterator<Item = (u32, f64)> + '_ {
+        self.capacities
+            .iter()
+            .zip(&self.hits)
+            .map(|(&capacity, &hits)| {
+                let ratio = match self.accesses {
+                    0 => 0.0,
+                    n => hits as f64 / n as f64,
+                };
+                (capacity, ratio)
+            })
+    }
+}
+
+/// The splitmix64 finalizer, so that sampling is uniform even for sequential keys
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cyclic_scan() {
+        let mut exact = HitRatioEstimator::new(&[50, 100, 200], 1.0);
+        let mut sampled = HitRatioEstimator::new(&[500, 2000], 0.25);
+        for _ in 0..10 {
+            for key in 0..1000 {
+                exact.access(key % 100);
+                sampled.access(key);
+            }
+        }
+        assert!(exact.hit_ratios().eq([(50, 0.0), (100, 0.99), (200, 0.99)]));
+        let ratios = sampled.hit_ratios().map(|(_, r)| r).collect::<Vec<_>>();
+        assert!(ratios[0] < 0.1);
+        assert!((0.85..0.95).contains(&ratios[1]));
+    }
+}
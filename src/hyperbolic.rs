@@ -0,0 +1,174 @@
+//! Hyperbolic caching with sampled eviction
+
+use alloc::boxed::Box;
+
+use crate::{rng::Rng, NONE};
+
+/// A fixed-capacity table that evicts according to hyperbolic caching
+///
+/// Each entry's priority is the number of times it has been used divided by the time since it was
+/// inserted, measured by a logical clock that ticks on every insert and use. Rather than keeping
+/// entries ordered, eviction samples a handful of slots at random and evicts the one with the
+/// lowest priority, so uses cost a counter increment.
+pub struct HyperbolicSlab<T> {
+    slots: Box<[Slot<T>]>,
+    len: u32,
+    /// First unused slot
+    free: u32,
+    /// Number of slots examined per eviction
+    samples: u32,
+    clock: u64,
+    rng: Rng,
+}
+
+impl<T> HyperbolicSlab<T> {
+    /// Create a [`HyperbolicSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        Self::with_samples(capacity, DEFAULT_SAMPLES)
+    }
+
+    /// Create a [`HyperbolicSlab`] that holds at most `capacity` values and examines `samples`
+    /// slots per eviction
+    ///
+    /// More samples approximate the ideal victim more closely, at a higher cost per eviction.
+    pub fn with_samples(capacity: u32, samples: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        assert!(capacity != u32::MAX, "capacity too large");
+        assert!(samples != 0, "samples must be nonzero");
+        Self {
+            slots: (0..capacity)
+                .map(|n| Slot {
+                    value: None,
+                    uses: 0,
+                    inserted: 0,
+                    next: if n + 1 == capacity { NONE } else { n + 1 },
+                })
+                .collect(),
+            len: 0,
+            free: 0,
+            samples,
+            clock: 0,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Insert a value, returning the slot it was stored in and the slot and value of any entry
+    /// evicted to make room
+    ///
+    /// When an entry is evicted, the new value takes over its slot.
+    pub fn insert(&mut self, value: T) -> (u32, Option<(u32, T)>) {
+        self.clock += 1;
+        let evicted = match self.free {
+            NONE => {
+                let slot = self.sample();
+                Some((slot, self.remove(slot)))
+            }
+            _ => None,
+        };
+        let slot = self.free;
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        self.free = entry.next;
+        entry.value = Some(value);
+        entry.uses = 1;
+        entry.inserted = self.clock;
+        self.len += 1;
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        let value = entry.value.take().expect("removing empty slot");
+        entry.next = self.free;
+        self.free = slot;
+        self.len -= 1;
+        value
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        self.clock += 1;
+        let entry = &mut self.slots[slot as usize];
+        entry.uses = entry.uses.saturating_add(1);
+        entry.value.as_mut().unwrap()
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Find the lowest-priority slot among a random sample, when every slot is occupied
+    fn sample(&mut self) -> u32 {
+        let mut best = self.rng.below(self.capacity());
+        for _ in 1..self.samples {
+            let candidate = self.rng.below(self.capacity());
+            if self.priority_lt(candidate, best) {
+                best = candidate;
+            }
+        }
+        best
+    }
+
+    /// Whether `a` has a lower priority than `b`
+    fn priority_lt(&self, a: u32, b: u32) -> bool {
+        let a = &self.slots[a as usize];
+        let b = &self.slots[b as usize];
+        // a.uses / a.age < b.uses / b.age, without division
+        let a_age = (self.clock - a.inserted).max(1);
+        let b_age = (self.clock - b.inserted).max(1);
+        u128::from(a.uses) * u128::from(b_age) < u128::from(b.uses) * u128::from(a_age)
+    }
+}
+
+/// Number of slots examined per eviction by [`HyperbolicSlab::new`]
+const DEFAULT_SAMPLES: u32 = 8;
+
+struct Slot<T> {
+    value: Option<T>,
+    uses: u32,
+    /// Logical time of insertion
+    inserted: u64,
+    /// Next slot in the free list
+    next: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_lowest_rate() {
+        // Sampling every slot many times over makes eviction effectively exact
+        let mut cache = HyperbolicSlab::with_samples(4, 64);
+        let slots = [0, 1, 2, 3].map(|x| cache.insert(x).0);
+        for (i, &slot) in slots.iter().enumerate() {
+            for _ in 0..(4 - i) * 4 {
+                cache.get_mut(slot);
+            }
+        }
+        let (_, evicted) = cache.insert(4);
+        assert_eq!(evicted, Some((slots[3], 3)));
+    }
+}
@@ -2,16 +2,222 @@
 
 #![warn(missing_docs)]
 #![no_std]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
-use alloc::boxed::Box;
-use core::{fmt, iter::FusedIterator, marker::PhantomData, ptr::addr_of_mut};
+#[cfg(feature = "alloc")]
+use allocator_api2::{
+    alloc::{Allocator, Global},
+    vec::Vec,
+};
+use core::{fmt, iter::FusedIterator, mem::MaybeUninit};
+#[cfg(feature = "alloc")]
+use core::{
+    iter,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr,
+};
 
+use crate::list::Link;
+#[cfg(feature = "trace")]
+use crate::trace::{TraceEvent, TraceKind};
+#[cfg(feature = "alloc")]
+use crate::{
+    bitset::{BitSet, Bits},
+    rng::Rng,
+};
+
+#[cfg(feature = "alloc")]
+mod arc;
+mod array;
+#[cfg(all(target_has_atomic = "64", feature = "alloc"))]
+mod atomic;
+#[cfg(feature = "alloc")]
+mod auxiliary;
+#[cfg(feature = "alloc")]
+mod bitset;
+#[cfg(feature = "alloc")]
+mod checked;
+#[cfg(feature = "alloc")]
+mod clock;
+#[cfg(feature = "alloc")]
+mod clock_pro;
+mod compact;
+#[cfg(feature = "critical-section")]
+mod critical_section;
+#[cfg(feature = "alloc")]
+mod dense;
+#[cfg(feature = "doorkeeper")]
+mod doorkeeper;
+#[cfg(feature = "alloc")]
+mod encoding;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "alloc")]
+mod gdsf;
+#[cfg(feature = "alloc")]
+mod ghost;
+#[cfg(feature = "alloc")]
+mod hit_ratio;
+#[cfg(feature = "alloc")]
+mod hyperbolic;
+#[cfg(feature = "alloc")]
+mod lirs;
+mod list;
+#[cfg(all(test, loom, feature = "alloc"))]
+mod loom_models;
+#[cfg(feature = "hashbrown")]
+mod lru_cache;
+#[cfg(feature = "hashbrown")]
+mod map;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "alloc")]
+mod mq;
+#[cfg(feature = "alloc")]
+mod multi_list;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "alloc")]
+mod partitioned;
+#[cfg(feature = "alloc")]
+mod primitives;
+#[cfg(feature = "alloc")]
+mod priority;
+#[cfg(feature = "alloc")]
+mod ranked;
+#[cfg(feature = "alloc")]
+mod raw;
+#[cfg(feature = "repr-c")]
+mod repr_c;
+#[cfg(feature = "alloc")]
+mod rng;
+#[cfg(feature = "alloc")]
+mod s3_fifo;
+#[cfg(feature = "alloc")]
+mod sampled;
+#[cfg(feature = "std")]
+mod sharded;
+#[cfg(feature = "alloc")]
+mod sieve;
+#[cfg(feature = "alloc")]
+mod sketch;
+#[cfg(feature = "alloc")]
+mod small;
+#[cfg(feature = "arc-swap")]
+mod snapshot;
+#[cfg(feature = "alloc")]
+mod sort;
+#[cfg(feature = "alloc")]
+mod stable;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "alloc")]
+mod tag_index;
+#[cfg(feature = "alloc")]
+mod tiny_lfu;
+#[cfg(feature = "alloc")]
+pub mod trace;
+#[cfg(feature = "wasm-bindgen")]
+mod wasm;
+
+#[cfg(feature = "alloc")]
+pub use arc::ArcSlab;
+pub use array::LruArray;
+#[cfg(all(target_has_atomic = "64", feature = "alloc"))]
+pub use atomic::AtomicLruSlab;
+#[cfg(feature = "alloc")]
+pub use auxiliary::AuxLruSlab;
+#[cfg(feature = "alloc")]
+pub use checked::CheckedLruSlab;
+#[cfg(feature = "alloc")]
+pub use clock::ClockSlab;
+#[cfg(feature = "alloc")]
+pub use clock_pro::ClockProSlab;
+pub use compact::{CompactIter, CompactLruArray};
+#[cfg(feature = "critical-section")]
+pub use critical_section::CsLruSlab;
+#[cfg(feature = "alloc")]
+pub use dense::DenseLruSlab;
+#[cfg(feature = "doorkeeper")]
+pub use doorkeeper::Doorkeeper;
+#[cfg(feature = "alloc")]
+pub use encoding::DecodeError;
+#[cfg(feature = "alloc")]
+pub use gdsf::GdsfSlab;
+#[cfg(feature = "alloc")]
+pub use ghost::GhostList;
+#[cfg(feature = "alloc")]
+pub use hit_ratio::HitRatioEstimator;
+#[cfg(feature = "alloc")]
+pub use hyperbolic::HyperbolicSlab;
+#[cfg(feature = "alloc")]
+pub use lirs::LirsSlab;
+#[cfg(feature = "hashbrown")]
+pub use lru_cache::LruCache;
+#[cfg(feature = "hashbrown")]
+pub use map::LruMap;
+#[cfg(feature = "metrics")]
+pub use metrics::SlabMetrics;
+#[cfg(feature = "alloc")]
+pub use mq::MqSlab;
+#[cfg(feature = "alloc")]
+pub use partitioned::PartitionedSlab;
+#[cfg(feature = "alloc")]
+pub use priority::PrioritySlab;
+#[cfg(feature = "alloc")]
+pub use ranked::RankedLruSlab;
+#[cfg(feature = "alloc")]
+pub use raw::{RawParts, RawSlot};
+#[cfg(feature = "repr-c")]
+pub use repr_c::ReprCSlab;
+#[cfg(feature = "alloc")]
+pub use s3_fifo::S3FifoSlab;
+#[cfg(feature = "alloc")]
+pub use sampled::SampledSlab;
+#[cfg(feature = "std")]
+pub use sharded::ShardedLruSlab;
+#[cfg(feature = "alloc")]
+pub use sieve::SieveSlab;
+#[cfg(feature = "alloc")]
+pub use small::SmallLruSlab;
+#[cfg(feature = "arc-swap")]
+pub use snapshot::{SnapshotReader, SnapshotWriter};
+#[cfg(feature = "alloc")]
+pub use stable::StableLruSlab;
+#[cfg(feature = "stats")]
+pub use stats::Stats;
+#[cfg(feature = "std")]
+pub use sync::{Guard, ReadGuard, SyncLruSlab};
+#[cfg(feature = "alloc")]
+pub use tiny_lfu::TinyLfuSlab;
+#[cfg(feature = "wasm-bindgen")]
+pub use wasm::JsLruSlab;
+
+#[cfg(feature = "alloc")]
 /// A random-access table that maintains an LRU list in constant time
-#[derive(Clone)]
-pub struct LruSlab<T> {
-    slots: Box<[Slot<T>]>,
+///
+/// Storage is allocated from `A`, which may be any [`Allocator`] from the `allocator-api2` crate,
+/// or from `core` with the `nightly` feature.
+///
+/// With the `zeroize` feature, a slot's storage is wiped whenever its element is removed or
+/// dropped, so that no copy lingers once the slot is vacant.
+pub struct LruSlab<T, A: Allocator = Global> {
+    /// Initialized where the corresponding link is occupied
+    values: Vec<MaybeUninit<T>, A>,
+    /// Kept apart from values so that list operations touch only dense link records
+    links: Vec<Link, A>,
+    meta: Vec<Meta, A>,
+    /// Occupied slots, for scans that ignore recency
+    occupied: BitSet<A>,
     /// Most recently used
     head: u32,
     /// Least recently used
@@ -20,30 +226,156 @@ pub struct LruSlab<T> {
     free: u32,
     /// Number of occupied slots
     len: u32,
+    /// Greatest `len` since creation or `reset_peak_len`
+    peak_len: u32,
+    /// Whether the old sublist is maintained, which begins with the first `insert_cold`
+    track_old: bool,
+    /// Most recently used slot of the old sublist, a suffix of the LRU list
+    midpoint: u32,
+    /// Number of slots in the old sublist
+    old_len: u32,
+    /// Accesses recorded by `record_access` but not yet applied
+    accesses: Vec<u32, A>,
+    /// Number of times a slot has been linked at the head, modulo 2^32
+    clock: u32,
+    /// Accessed slots linked at the head at most this many links ago aren't moved, if nonzero
+    freshen_threshold: u32,
+    /// Chance, in units of 2^-32, that an access which isn't skipped moves the slot
+    freshen_odds: u64,
+    rng: Rng,
+    /// Current epoch, if accesses are coalesced by epoch
+    epoch: Option<u32>,
+    /// Value of `clock` when the current epoch began
+    epoch_start: u32,
+    /// Whether the free list is kept in ascending order
+    reuse_lowest: bool,
+    /// Occupancy below which storage should eventually be shrunk
+    shrink_below: f64,
+    /// Number of consecutive insertions and removals below `shrink_below` after which storage
+    /// should be shrunk, or zero to never shrink automatically
+    shrink_after: u32,
+    /// Number of consecutive insertions and removals below `shrink_below`
+    sparse_ops: u32,
+    #[cfg(feature = "stats")]
+    stats: Stats,
+    /// Receives each access, if set
+    #[cfg(feature = "trace")]
+    trace_sink: Option<TraceSink>,
+    /// Logical time of the next access to be traced
+    #[cfg(feature = "trace")]
+    trace_time: u64,
 }
 
+#[cfg(feature = "alloc")]
 impl<T> LruSlab<T> {
     /// Create an empty [`LruSlab`]
-    pub fn new() -> Self {
-        Self::with_capacity(0)
+    ///
+    /// Doesn't allocate, so may be used to initialize statics.
+    pub const fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            links: Vec::new(),
+            meta: Vec::new(),
+            occupied: BitSet::new_in(Global),
+            head: NONE,
+            tail: NONE,
+            free: NONE,
+            len: 0,
+            peak_len: 0,
+            track_old: false,
+            midpoint: NONE,
+            old_len: 0,
+            accesses: Vec::new(),
+            clock: 0,
+            freshen_threshold: 0,
+            freshen_odds: ALWAYS,
+            rng: Rng::new(),
+            epoch: None,
+            epoch_start: 0,
+            reuse_lowest: false,
+            shrink_below: 0.0,
+            shrink_after: 0,
+            sparse_ops: 0,
+            #[cfg(feature = "stats")]
+            stats: Stats::new(),
+            #[cfg(feature = "trace")]
+            trace_sink: None,
+            #[cfg(feature = "trace")]
+            trace_time: 0,
+        }
     }
 
     /// Create an [`LruSlab`] that can store at least `capacity` elements without reallocating
     pub fn with_capacity(capacity: u32) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    /// Like `From<Vec<T>>`, but returning `values` if there are too many to index
+    pub fn try_from_vec(values: alloc::vec::Vec<T>) -> Result<Self, alloc::vec::Vec<T>> {
+        match u32::try_from(values.len()) {
+            Ok(len) if len != u32::MAX => Ok(values.into()),
+            _ => Err(values),
+        }
+    }
+
+    /// Create an [`LruSlab`] whose LRU order matches `iter`, from most to least recently used
+    ///
+    /// The `n`th element is stored in slot `n`. Unlike [`FromIterator`], under which the last
+    /// element becomes the most recently used, this suits restoring a persisted recency order.
+    pub fn from_ordered_iter(iter: impl IntoIterator<Item = T>) -> Self {
+        let iter = iter.into_iter();
+        let mut slab = LruSlab::with_capacity(u32::try_from(iter.size_hint().0).unwrap());
+        for x in iter {
+            slab.insert_at_tail(x);
+        }
+        slab
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator + Clone> LruSlab<T, A> {
+    /// Create an empty [`LruSlab`] that allocates from `alloc`
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_capacity_in(0, alloc)
+    }
+
+    /// Create an [`LruSlab`] that allocates from `alloc` and can store at least `capacity`
+    /// elements without reallocating
+    pub fn with_capacity_in(capacity: u32, alloc: A) -> Self {
         assert!(capacity != u32::MAX, "capacity too large");
-        Self {
-            slots: (0..capacity)
-                .map(|n| Slot {
-                    value: None,
-                    prev: NONE,
-                    next: if n + 1 == capacity { NONE } else { n + 1 },
-                })
-                .collect(),
+        let mut slab = Self {
+            values: Vec::new_in(alloc.clone()),
+            links: Vec::new_in(alloc.clone()),
+            meta: Vec::new_in(alloc.clone()),
+            occupied: BitSet::new_in(alloc.clone()),
             head: NONE,
             tail: NONE,
-            free: if capacity == 0 { NONE } else { 0 },
+            free: NONE,
             len: 0,
-        }
+            peak_len: 0,
+            track_old: false,
+            midpoint: NONE,
+            old_len: 0,
+            accesses: Vec::new_in(alloc),
+            clock: 0,
+            freshen_threshold: 0,
+            freshen_odds: ALWAYS,
+            rng: Rng::new(),
+            epoch: None,
+            epoch_start: 0,
+            reuse_lowest: false,
+            shrink_below: 0.0,
+            shrink_after: 0,
+            sparse_ops: 0,
+            #[cfg(feature = "stats")]
+            stats: Stats::new(),
+            #[cfg(feature = "trace")]
+            trace_sink: None,
+            #[cfg(feature = "trace")]
+            trace_time: 0,
+        };
+        slab.grow(capacity);
+        slab
     }
 
     /// Whether no elements are stored
@@ -58,7 +390,144 @@ impl<T> LruSlab<T> {
 
     /// Number of elements that can be stored without reallocating
     pub fn capacity(&self) -> u32 {
-        self.slots.len() as u32
+        self.links.len() as u32
+    }
+
+    /// Counts of operations performed since creation or the last
+    /// [`reset_stats`](Self::reset_stats)
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Zero the counts returned by [`stats`](Self::stats)
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+
+    /// Verify that internal bookkeeping is consistent, returning the first violation found
+    ///
+    /// Takes time proportional to the capacity. Useful for testing code that relies on unchecked
+    /// accesses.
+    pub fn check_invariants(&self) -> Result<(), InvariantError> {
+        let fail = |slot, reason| Err(InvariantError { slot, reason });
+        let capacity = self.capacity();
+        let mut seen = alloc::vec![false; capacity as usize];
+
+        let young = match self.track_old {
+            true => self.len - self.old_len,
+            false => self.len,
+        };
+        let (mut prev, mut slot, mut count) = (NONE, self.head, 0);
+        while slot != NONE {
+            if slot >= capacity {
+                return fail(Some(slot), "LRU list link out of bounds");
+            }
+            if mem::replace(&mut seen[slot as usize], true) {
+                return fail(Some(slot), "slot linked more than once");
+            }
+            let link = &self.links[slot as usize];
+            if link.prev == FREE || !self.occupied.contains(slot) {
+                return fail(Some(slot), "vacant slot in LRU list");
+            }
+            if link.prev != prev {
+                return fail(Some(slot), "prev link disagrees with next link");
+            }
+            if self.meta[slot as usize].old != (count >= young) {
+                return fail(Some(slot), "old sublist isn't the expected suffix");
+            }
+            if count == young && slot != self.midpoint {
+                return fail(Some(slot), "midpoint isn't the head of the old sublist");
+            }
+            (prev, slot, count) = (slot, link.next, count + 1);
+        }
+        if prev != self.tail {
+            return fail(Some(self.tail), "tail isn't the end of the LRU list");
+        }
+        if count != self.len {
+            return fail(None, "length disagrees with the LRU list");
+        }
+        if self.track_old && self.old_len != (u64::from(self.len) * 3 / 8) as u32 {
+            return fail(None, "old sublist is unbalanced");
+        }
+        if self.old_len == 0 && self.midpoint != NONE {
+            return fail(Some(self.midpoint), "midpoint set without old sublist");
+        }
+
+        let mut slot = self.free;
+        while slot != NONE {
+            if slot >= capacity {
+                return fail(Some(slot), "free list link out of bounds");
+            }
+            if mem::replace(&mut seen[slot as usize], true) {
+                return fail(Some(slot), "slot linked more than once");
+            }
+            if self.links[slot as usize].prev != FREE || self.occupied.contains(slot) {
+                return fail(Some(slot), "occupied slot in free list");
+            }
+            if self.meta[slot as usize].pins != 0 {
+                return fail(Some(slot), "vacant slot is pinned");
+            }
+            slot = self.links[slot as usize].next;
+        }
+        match seen.iter().position(|&x| !x) {
+            Some(slot) => fail(Some(slot as u32), "slot in neither list"),
+            None => Ok(()),
+        }
+    }
+
+    /// Render the LRU list and free list in Graphviz DOT format, for debugging
+    ///
+    /// Slots in the old sublist are shaded, and free list links are dashed.
+    pub fn write_dot(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        writeln!(out, "digraph lru_slab {{")?;
+        writeln!(out, "    rankdir=LR;")?;
+        writeln!(out, "    node [shape=box];")?;
+        for slot in 0..self.capacity() {
+            let (state, style) = if !self.is_occupied(slot) {
+                ("vacant", "dotted")
+            } else if self.meta[slot as usize].old {
+                ("old", "filled")
+            } else {
+                ("occupied", "solid")
+            };
+            writeln!(
+                out,
+                "    {slot} [label=\"{slot} ({state})\", style={style}];"
+            )?;
+        }
+        for (name, first, style) in [
+            ("head", self.head, "solid"),
+            ("tail", self.tail, "solid"),
+            ("free", self.free, "dashed"),
+        ] {
+            writeln!(out, "    {name} [shape=plaintext];")?;
+            if first != NONE {
+                writeln!(out, "    {name} -> {first} [style={style}];")?;
+            }
+        }
+        for slot in 0..self.capacity() {
+            let next = self.links[slot as usize].next;
+            if next != NONE {
+                let style = if self.is_occupied(slot) {
+                    "solid"
+                } else {
+                    "dashed"
+                };
+                writeln!(out, "    {slot} -> {next} [style={style}];")?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    /// Number of bytes of heap storage held, including bookkeeping
+    pub fn allocated_bytes(&self) -> usize {
+        self.values.capacity() * mem::size_of::<T>()
+            + self.links.capacity() * mem::size_of::<Link>()
+            + self.meta.capacity() * mem::size_of::<Meta>()
+            + self.occupied.allocated_bytes()
+            + self.accesses.capacity() * mem::size_of::<u32>()
     }
 
     /// The slot that will be returned by the next call to `insert`, unless `remove` is called first
@@ -69,87 +538,883 @@ impl<T> LruSlab<T> {
         }
     }
 
-    /// Insert a value, returning the slot it was stored in
+    /// Greatest number of elements stored at once since creation or the last call to
+    /// [`reset_peak_len`](Self::reset_peak_len)
+    pub fn peak_len(&self) -> u32 {
+        self.peak_len
+    }
+
+    /// Restart [`peak_len`](Self::peak_len) tracking from the current length
+    pub fn reset_peak_len(&mut self) {
+        self.peak_len = self.len;
+    }
+
+    /// The age of the elements at each of `percentiles`, or `None` if the slab is empty
+    ///
+    /// An element's age is the number of times a slot was moved to the head of the LRU list since
+    /// it was inserted or last moved there itself. Percentiles are in `0.0..=100.0` and are
+    /// resolved to the nearest rank, so e.g. `[50.0, 90.0, 99.0]` yields the median, p90, and p99
+    /// ages. Takes time proportional to capacity plus `n log n` in the number of elements.
+    pub fn age_percentiles<const N: usize>(&self, percentiles: [f64; N]) -> Option<[u32; N]> {
+        if self.len == 0 {
+            return None;
+        }
+        let mut ages = self
+            .occupied
+            .iter()
+            .map(|slot| self.clock.wrapping_sub(self.meta[slot as usize].linked_at))
+            .collect::<alloc::vec::Vec<_>>();
+        ages.sort_unstable();
+        let n = ages.len();
+        Some(percentiles.map(|p| {
+            let exact = p.clamp(0.0, 100.0) / 100.0 * n as f64;
+            let rank = exact as usize + usize::from((exact as usize as f64) < exact);
+            ages[rank.clamp(1, n) - 1]
+        }))
+    }
+
+    /// Pass every subsequent insertion, use, and removal of a slot to `sink`, replacing any
+    /// previous sink
+    ///
+    /// Uses are recorded whether or not they change the LRU order. Clones don't inherit the sink.
+    #[cfg(feature = "trace")]
+    pub fn set_trace_sink(&mut self, sink: impl FnMut(TraceEvent) + Send + Sync + 'static) {
+        self.trace_sink = Some(alloc::boxed::Box::new(sink));
+    }
+
+    /// Stop passing accesses to the sink set by [`set_trace_sink`](Self::set_trace_sink)
+    #[cfg(feature = "trace")]
+    pub fn clear_trace_sink(&mut self) {
+        self.trace_sink = None;
+    }
+
+    #[cfg(feature = "trace")]
+    fn record(&mut self, kind: TraceKind, slot: u32) {
+        if let Some(sink) = &mut self.trace_sink {
+            sink(TraceEvent {
+                time: self.trace_time,
+                slot,
+                kind,
+            });
+            self.trace_time += 1;
+        }
+    }
+
+    /// Number of vacant slots, which can be filled without reallocating
+    pub fn free_len(&self) -> u32 {
+        self.capacity() - self.len
+    }
+
+    /// The highest occupied slot, if any
+    ///
+    /// Parallel arrays indexed by slot need only cover slots up to this one. Takes time
+    /// proportional to the number of vacant slots above the result.
+    pub fn max_occupied_slot(&self) -> Option<u32> {
+        self.occupied.iter().next_back()
+    }
+
+    /// Iterate over the vacant slots, in the order they'll be filled
+    pub fn vacant_slots(&self) -> impl Iterator<Item = u32> + '_ {
+        let first = (self.free != NONE).then_some(self.free);
+        iter::successors(first, |&slot| match self.links[slot as usize].next {
+            NONE => None,
+            next => Some(next),
+        })
+    }
+
+    /// Get a handle to the slot that will be returned by the next call to `insert`, allowing the
+    /// inserted value to depend on it
+    pub fn vacant_entry(&mut self) -> VacantEntry<'_, T, A> {
+        VacantEntry { slab: self }
+    }
+
+    /// Get unique access to the element in `slot`, which is removed when the returned guard is
+    /// dropped unless it's [`kept`](TakeGuard::keep)
+    ///
+    /// Accessing the element through the guard doesn't mark it as used.
+    pub fn take_guard(&mut self, slot: u32) -> TakeGuard<'_, T, A> {
+        assert!(self.is_occupied(slot), "guarding empty slot");
+        TakeGuard { slab: self, slot }
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&mut self, value: T) -> u32 {
+        let id = self.insert_unlinked(value);
+        self.link_at_head(id);
+        self.rebalance();
+        id
+    }
+
+    /// Insert a value, returning the slot it was stored in and unique access to it
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert_and_get_mut(&mut self, value: T) -> (u32, &mut T) {
+        let slot = self.insert(value);
+        // Safety: just inserted
+        (slot, unsafe { self.peek_unchecked_mut(slot) })
+    }
+
+    /// Insert a value as if by [`insert`](Self::insert), failing instead of panicking or aborting
+    /// if storage for it can't be reserved
+    pub fn try_insert(&mut self, value: T) -> Result<u32, InsertError<T>> {
+        if self.free == NONE {
+            let capacity = self.capacity().max(2).saturating_mul(2).min(u32::MAX - 1);
+            let result = match capacity > self.capacity() {
+                true => self.try_grow(capacity),
+                false => Err(ReserveError::CapacityOverflow),
+            };
+            if let Err(reason) = result {
+                return Err(InsertError { value, reason });
+            }
+            count!(self, grows);
+        }
+        let Some(id) = self.alloc() else {
+            // Unreachable, since growth succeeded, but this path mustn't panic
+            return Err(InsertError {
+                value,
+                reason: ReserveError::CapacityOverflow,
+            });
+        };
+        self.store(id, value);
+        self.link_at_head(id);
+        self.rebalance();
+        Ok(id)
+    }
+
+    /// Ensure that at least `additional` more elements can be stored without reallocating,
+    /// failing instead of panicking or aborting if the storage can't be reserved
+    pub fn try_reserve(&mut self, additional: u32) -> Result<(), ReserveError> {
+        let needed = self
+            .len
+            .checked_add(additional)
+            .ok_or(ReserveError::CapacityOverflow)?;
+        if needed > self.capacity() {
+            self.try_grow(needed)?;
+            count!(self, grows);
+        }
+        Ok(())
+    }
+
+    /// Insert each of `values` in turn, as if by [`insert`](Self::insert), extending `slots` with
+    /// the slot each was stored in
+    ///
+    /// Storage is reserved up front according to the iterator's size hint, so loading many values
+    /// reallocates at most once when the hint is accurate.
+    pub fn insert_many(
+        &mut self,
+        values: impl IntoIterator<Item = T>,
+        slots: &mut impl Extend<u32>,
+    ) {
+        let values = values.into_iter();
+        let needed = u32::try_from(values.size_hint().0).expect("too many values");
+        span!(if needed as usize >= LARGE_BATCH, "insert_many", needed);
+        if needed > self.capacity() - self.len {
+            let required = self.len.checked_add(needed).expect("too many values");
+            // Grow geometrically, like `insert`, so that repeated small batches stay linear
+            let doubled = self.capacity().saturating_mul(2).min(u32::MAX - 1);
+            self.grow(required.max(doubled));
+            count!(self, grows);
+        }
+        slots.extend(values.map(|value| {
+            let id = self.insert_unlinked(value);
+            self.link_at_head(id);
+            id
+        }));
+        self.rebalance();
+    }
+
+    /// Insert a value at the midpoint of the LRU list, returning the slot it was stored in
+    ///
+    /// The returned slot is placed ahead of the least recently used 3/8ths of the table, so it's
+    /// evicted sooner than entries inserted with [`insert`](Self::insert) unless it's accessed
+    /// first. This protects frequently used entries from being flushed out by a scan of values
+    /// that are each used only once.
+    ///
+    /// The first call takes time proportional to the number of elements, to locate the midpoint,
+    /// which is maintained from then on.
+    pub fn insert_cold(&mut self, value: T) -> u32 {
+        if !self.track_old {
+            self.track_old = true;
+            self.rebalance();
+        }
+        let id = self.insert_unlinked(value);
+        self.link_at_midpoint(id);
+        self.rebalance();
+        id
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the least recently used, so it will be the first to be
+    /// returned by [`lru`](Self::lru) unless it's accessed. Useful for speculative entries.
+    pub fn insert_at_tail(&mut self, value: T) -> u32 {
+        let id = self.insert_unlinked(value);
+        self.link_at_tail(id);
+        self.rebalance();
+        id
+    }
+
+    /// Store a value in a vacant slot, growing if necessary, without linking it into the LRU list
+    fn insert_unlinked(&mut self, value: T) -> u32 {
+        let id = match self.alloc() {
+            Some(id) => id,
+            None => {
+                self.grow(2 * self.capacity().max(2));
+                count!(self, grows);
+                self.alloc().unwrap()
+            }
+        };
+        self.store(id, value);
+        id
+    }
+
+    /// Store a value in `slot`, just taken from the free list, without linking it into the LRU
+    /// list
+    fn store(&mut self, slot: u32, value: T) {
+        debug_assert!(!self.is_occupied(slot), "corrupt free list");
+        // Safety: slots on the free list are in bounds
+        unsafe { self.values.get_unchecked_mut(slot as usize) }.write(value);
+        self.occupied.insert(slot);
+        // Marks the slot occupied until it's linked
+        self.link_mut(slot).prev = NONE;
+        self.meta_mut(slot).linked_at = self.clock;
+        self.len += 1;
+        self.peak_len = self.peak_len.max(self.len);
+        self.note_occupancy();
+        count!(self, inserts);
+        record!(self, Insert, slot);
+    }
+
+    /// Get the least recently used slot that isn't pinned, if any
+    ///
+    /// Takes time proportional to the number of pinned slots less recently used than the result.
+    pub fn lru(&self) -> Option<u32> {
+        if self.tail == NONE {
+            debug_assert_eq!(self.head, NONE);
+            return None;
+        }
+        let mut slot = self.tail;
+        while slot != NONE && self.meta(slot).pins != 0 {
+            slot = self.link(slot).prev;
+        }
+        (slot != NONE).then_some(slot)
+    }
+
+    /// Remove the least recently used element that isn't pinned, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        let slot = self.lru()?;
+        count!(self, evictions);
+        // Safety: `lru` only returns linked slots
+        let value = unsafe { self.release_unchecked(slot) };
+        self.rebalance();
+        Some((slot, value))
+    }
+
+    /// Remove up to `n` of the least recently used elements that aren't pinned, yielding their
+    /// slots and values
+    ///
+    /// Elements are removed as the iterator is advanced, from least recently used onwards.
+    pub fn evict_n(&mut self, n: u32) -> EvictN<'_, T, A> {
+        EvictN {
+            slab: self,
+            remaining: n,
+        }
+    }
+
+    /// Remove the least recently used elements that aren't pinned for as long as `pred` returns
+    /// `true` for them, yielding their slots and values
+    ///
+    /// Elements are removed as the iterator is advanced. Iteration ends at the first element for
+    /// which `pred` returns `false`, which is retained.
+    pub fn evict_while<F>(&mut self, pred: F) -> EvictWhile<'_, T, F, A>
+    where
+        F: FnMut(u32, &T) -> bool,
+    {
+        EvictWhile {
+            slab: self,
+            pred,
+            done: false,
+        }
+    }
+
+    /// Drop the least recently used elements until at most `len` remain
+    ///
+    /// Pinned elements are retained, so more than `len` may remain if too many are pinned.
+    pub fn truncate_to(&mut self, len: u32) {
+        let excess = self.len.saturating_sub(len);
+        span!("truncate_to", len = self.len, excess);
+        self.evict_n(excess).for_each(drop);
+    }
+
+    /// Drop every element, including pinned ones, retaining the allocated capacity
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+        self.accesses.clear();
+    }
+
+    /// Remove up to `n` of the least recently used elements that aren't pinned, returning them in
+    /// a new [`LruSlab`] with their relative order preserved
+    ///
+    /// Elements are assigned new slots in the returned slab.
+    pub fn split_off_lru(&mut self, n: u32) -> Self {
+        span!("split_off_lru", len = self.len, n);
+        let mut other = Self::with_capacity_in(n.min(self.len), self.links.allocator().clone());
+        for (_, value) in self.evict_n(n) {
+            // Evicted from least recently used onwards, so each is more recent than the last
+            other.insert(value);
+        }
+        other
+    }
+
+    /// Divide the elements between two new slabs, the first holding those for which `pred`
+    /// returns `true` and the second the rest
+    ///
+    /// The relative order and pins of the elements are preserved in each, but they're assigned new
+    /// slots.
+    pub fn partition(mut self, mut pred: impl FnMut(u32, &T) -> bool) -> (Self, Self) {
+        self.flush();
+        let alloc = self.links.allocator().clone();
+        let mut yes = Self::new_in(alloc.clone());
+        let mut no = Self::new_in(alloc);
+        while self.head != NONE {
+            let old = self.head;
+            let pins = self.meta[old as usize].pins;
+            // Safety: `old` is linked and hence occupied
+            let target = if pred(old, unsafe { self.values[old as usize].assume_init_ref() }) {
+                &mut yes
+            } else {
+                &mut no
+            };
+            let new = target.insert_unlinked(self.release(old));
+            target.link_at_tail(new);
+            target.meta[new as usize].pins = pins;
+        }
+        yes.rebalance();
+        no.rebalance();
+        (yes, no)
+    }
+
+    /// Move every element of `other` into `self`, as less recently used than every existing
+    /// element, leaving `other` empty
+    ///
+    /// The relative order and pins of the moved elements are preserved. `f` is called with the
+    /// original and new slot of each moved element.
+    pub fn append(&mut self, other: &mut Self, mut f: impl FnMut(u32, u32)) {
+        span!(if other.len as usize >= LARGE_BATCH, "append", len = other.len);
+        let vacant = self.capacity() - self.len;
+        if other.len > vacant {
+            self.grow(self.len.checked_add(other.len).expect("too many elements"));
+            count!(self, grows);
+        }
+        while other.head != NONE {
+            let old = other.head;
+            let pins = other.meta[old as usize].pins;
+            let new = self.insert_unlinked(other.release(old));
+            self.link_at_tail(new);
+            self.meta[new as usize].pins = pins;
+            f(old, new);
+        }
+        other.rebalance();
+        self.rebalance();
+    }
+
+    /// Exempt `slot` from being returned by [`lru`](Self::lru) until a matching call to
+    /// [`unpin`](Self::unpin)
+    ///
+    /// Pins are counted, so a slot may be pinned several times. Pinned slots can still be
+    /// accessed and moved in the LRU order, and may still be removed explicitly, which discards
+    /// their pins.
+    pub fn pin(&mut self, slot: u32) {
+        assert!(self.is_occupied(slot), "pinning empty slot");
+        let entry = &mut self.meta[slot as usize];
+        entry.pins = entry.pins.checked_add(1).expect("pin count overflow");
+    }
+
+    /// Release one pin on `slot`
+    pub fn unpin(&mut self, slot: u32) {
+        assert!(self.is_occupied(slot), "unpinning empty slot");
+        let entry = &mut self.meta[slot as usize];
+        entry.pins = entry
+            .pins
+            .checked_sub(1)
+            .expect("unpinning slot that isn't pinned");
+    }
+
+    /// Whether `slot` is pinned
+    pub fn is_pinned(&self, slot: u32) -> bool {
+        self.meta[slot as usize].pins != 0
+    }
+
+    /// Release storage for vacant slots above the highest occupied slot
+    ///
+    /// Slots never move, so vacant slots below the highest occupied slot are kept. Takes time
+    /// proportional to the number of vacant slots.
+    pub fn shrink_to_fit(&mut self) {
+        let capacity = self.max_occupied_slot().map_or(0, |slot| slot + 1);
+        if capacity < self.capacity() {
+            // Drop the released slots from the free list, preserving its order
+            let mut prev = NONE;
+            let mut slot = self.free;
+            while slot != NONE {
+                let next = self.links[slot as usize].next;
+                if slot >= capacity {
+                    match prev {
+                        NONE => self.free = next,
+                        _ => self.links[prev as usize].next = next,
+                    }
+                } else {
+                    prev = slot;
+                }
+                slot = next;
+            }
+            self.values.truncate(capacity as usize);
+            self.links.truncate(capacity as usize);
+            self.meta.truncate(capacity as usize);
+            self.occupied.truncate(capacity);
+        }
+        #[cfg(feature = "zeroize")]
+        if self.values.capacity() > self.values.len() {
+            let mut values = Vec::new_in(self.values.allocator().clone());
+            values.reserve_exact(self.values.len());
+            self.move_values(values);
+        }
+        self.values.shrink_to_fit();
+        self.links.shrink_to_fit();
+        self.meta.shrink_to_fit();
+        self.sparse_ops = 0;
+    }
+
+    /// Shrink storage with [`shrink_to_fit`](Self::shrink_to_fit) during
+    /// [`maintain`](Self::maintain) once `ops` consecutive insertions and removals each leave
+    /// fewer than `fraction` of the slots occupied
+    ///
+    /// An `ops` of zero, the default, disables automatic shrinking.
+    pub fn set_auto_shrink(&mut self, fraction: f64, ops: u32) {
+        self.shrink_below = fraction;
+        self.shrink_after = ops;
+        self.sparse_ops = 0;
+    }
+
+    /// Mark `slot` as modified since it was last written back, until the next
+    /// [`flush_dirty`](Self::flush_dirty)
+    ///
+    /// Vacating the slot clears the mark.
+    pub fn mark_dirty(&mut self, slot: u32) {
+        assert!(self.is_occupied(slot), "marking empty slot");
+        self.meta[slot as usize].dirty = true;
+    }
+
+    /// Whether `slot` is marked dirty
+    pub fn is_dirty(&self, slot: u32) -> bool {
+        self.is_occupied(slot) && self.meta[slot as usize].dirty
+    }
+
+    /// Iterate over the elements marked dirty, from least to most recently used
+    ///
+    /// Takes time proportional to the number of elements.
+    pub fn iter_dirty(&self) -> impl Iterator<Item = (u32, &T)> + '_ {
+        self.iter()
+            .rev()
+            .filter(|&(slot, _)| self.meta[slot as usize].dirty)
+    }
+
+    /// Pass each element marked dirty to `f`, from least to most recently used, and clear its mark
+    pub fn flush_dirty(&mut self, mut f: impl FnMut(u32, &T)) {
+        let mut slot = self.tail;
+        while slot != NONE {
+            if mem::take(&mut self.meta_mut(slot).dirty) {
+                // Safety: linked slots are occupied
+                f(slot, unsafe { self.peek_unchecked(slot) });
+            }
+            slot = self.link(slot).prev;
+        }
+    }
+
+    /// Whether to store new elements in the lowest vacant slot, rather than the most recently
+    /// vacated one
+    ///
+    /// Preferring low slots keeps the occupied ones dense after churn, for locality and for the
+    /// sake of parallel arrays indexed by slot. Vacating a slot then takes time proportional to
+    /// the number of occupied slots below it, in the worst case. Enabling this takes time
+    /// proportional to the capacity.
+    pub fn set_reuse_lowest(&mut self, enabled: bool) {
+        if enabled && !self.reuse_lowest {
+            self.sort_free_list();
+        }
+        self.reuse_lowest = enabled;
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let value = self.release(slot);
+        self.rebalance();
+        value
+    }
+
+    /// Remove the element stored in `slot`, returning it, or fail if `slot` is vacant
+    pub fn try_remove(&mut self, slot: u32) -> Result<T, VacantError> {
+        if !self.contains(slot) {
+            return Err(VacantError { slot });
+        }
+        // Safety: checked above
+        let value = unsafe { self.release_unchecked(slot) };
+        self.rebalance();
+        Ok(value)
+    }
+
+    /// Remove the elements stored in each of `slots`, passing each slot and its element to `f`
+    ///
+    /// Equivalent to calling [`remove`](Self::remove) on each slot in turn, but cheaper.
+    pub fn remove_many(&mut self, slots: &[u32], mut f: impl FnMut(u32, T)) {
+        span!(if slots.len() >= LARGE_BATCH, "remove_many", slots = slots.len());
+        for &slot in slots {
+            f(slot, self.release(slot));
+        }
+        self.rebalance();
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        assert!(self.is_occupied(slot), "accessing empty slot");
+        // Safety: checked above
+        unsafe { self.get_unchecked_mut(slot) }
+    }
+
+    /// Apply `f` to the element in `slot`, marking it as the most recently used only if `f`
+    /// returns `true`
+    pub fn update(&mut self, slot: u32, f: impl FnOnce(&mut T) -> bool) {
+        if f(self.peek_mut(slot)) {
+            self.freshen(slot);
+        }
+    }
+
+    /// Record an access to `slot` to be applied by the next call to [`flush`](Self::flush)
+    ///
+    /// Cheaper than [`get_mut`](Self::get_mut) when many slots are accessed in a row and the
+    /// intermediate order doesn't matter. Accesses are buffered up to a small fixed limit, beyond
+    /// which they're flushed automatically. An access to a slot that's removed before the flush
+    /// is discarded, unless the slot is reused in the meantime.
+    pub fn record_access(&mut self, slot: u32) {
+        if self.accesses.len() == ACCESS_BUFFER {
+            self.flush();
+        }
+        self.accesses.push(slot);
+    }
+
+    /// Apply the accesses recorded by [`record_access`](Self::record_access), in order
+    pub fn flush(&mut self) {
+        self.flush_n(self.accesses.len());
+    }
+
+    /// Perform at most about `budget` units of deferred work, returning whether any remains
+    ///
+    /// Lets callers spread bookkeeping over idle moments, e.g. once per iteration of an event
+    /// loop, rather than incurring it within a later operation. Applying one access recorded by
+    /// [`record_access`](Self::record_access) is one unit, as is shrinking storage per
+    /// [`set_auto_shrink`](Self::set_auto_shrink).
+    pub fn maintain(&mut self, budget: u32) -> bool {
+        let n = self.accesses.len().min(budget as usize);
+        if n != 0 {
+            self.flush_n(n);
+        }
+        if self.shrink_due() && budget as usize > n {
+            self.shrink_to_fit();
+        }
+        !self.accesses.is_empty() || self.shrink_due()
+    }
+
+    /// Track how long occupancy has been low enough to shrink automatically
+    fn note_occupancy(&mut self) {
+        if f64::from(self.len) < self.shrink_below * f64::from(self.capacity()) {
+            self.sparse_ops = self.sparse_ops.saturating_add(1);
+        } else {
+            self.sparse_ops = 0;
+        }
+    }
+
+    fn shrink_due(&self) -> bool {
+        self.shrink_after != 0 && self.sparse_ops >= self.shrink_after
+    }
+
+    /// Apply the first `n` recorded accesses
+    fn flush_n(&mut self, n: usize) {
+        for i in 0..n {
+            let slot = self.accesses[i];
+            if !self.contains(slot) {
+                continue;
+            }
+            count!(self, hits);
+            record!(self, Touch, slot);
+            if !self.is_fresh(slot) && self.roll_freshen() {
+                self.unlink(slot);
+                self.link_at_head(slot);
+            }
+        }
+        self.rebalance();
+        self.accesses.drain(..n);
+    }
+
+    /// Leave accessed slots where they are if at most `k` slots have been moved to the head of the
+    /// LRU list since they were
+    ///
+    /// This saves list updates for entries that are already among the most recently used, at the
+    /// cost of less precise recency. Zero, the default, moves a slot on every access.
+    pub fn set_freshen_threshold(&mut self, k: u32) {
+        self.freshen_threshold = k;
+    }
+
+    /// Move accessed slots to the head of the LRU list only with probability `p`, clamped to
+    /// `0.0..=1.0`
+    ///
+    /// Frequently used entries are still likely to be moved soon after they drift from the head,
+    /// so in large slabs this sharply reduces list updates at little cost in hit rate. One, the
+    /// default, moves a slot on every access. Decisions are drawn from a cheap internal generator
+    /// with a fixed seed, so they're reproducible but not unpredictable.
+    pub fn set_freshen_probability(&mut self, p: f64) {
+        self.freshen_odds = (p.clamp(0.0, 1.0) * ALWAYS as f64) as u64;
+    }
+
+    /// Begin epoch `n`, unless it's already current
+    ///
+    /// Once an epoch has been set, a slot is moved to the head of the LRU list by at most its first
+    /// access in each epoch, so repeated accesses within an epoch are cheap and recency is tracked
+    /// at the granularity of epochs, e.g. frames.
+    pub fn set_epoch(&mut self, n: u32) {
+        if self.epoch != Some(n) {
+            self.epoch = Some(n);
+            self.epoch_start = self.clock;
+        }
+    }
+
+    /// Begin the epoch after the current one, or epoch 0 if none has been set
+    pub fn advance_epoch(&mut self) {
+        self.set_epoch(self.epoch.map_or(0, |n| n.wrapping_add(1)));
+    }
+
+    /// Move the most recently used slot to the least recently used end, if there is one
+    pub fn rotate(&mut self) {
+        let slot = self.head;
+        if slot == self.tail {
+            return;
+        }
+        self.unlink(slot);
+        self.link_at_tail(slot);
+        self.rebalance();
+    }
+
+    /// Reverse the LRU order, so that the least recently used slot becomes the most recently used
+    ///
+    /// Takes time proportional to the number of elements.
+    pub fn reverse(&mut self) {
+        let mut slot = self.head;
+        while slot != NONE {
+            let link = self.link_mut(slot);
+            mem::swap(&mut link.prev, &mut link.next);
+            let next = link.prev;
+            let meta = self.meta_mut(slot);
+            meta.old = false;
+            meta.hot = false;
+            slot = next;
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+        self.midpoint = NONE;
+        self.old_len = 0;
+        self.rebalance();
+    }
+
+    /// Mark each of `slots` as more recently used than every other slot, keeping their given order
+    ///
+    /// Afterwards, `slots[0]` is the most recently used, followed by `slots[1]`, and so on.
+    pub fn touch_many(&mut self, slots: &[u32]) {
+        span!(if slots.len() >= LARGE_BATCH, "touch_many", slots = slots.len());
+        for &slot in slots.iter().rev() {
+            assert!(self.is_occupied(slot), "touching empty slot");
+            count!(self, hits);
+            record!(self, Touch, slot);
+            if self.head != slot {
+                self.unlink(slot);
+                self.link_at_head(slot);
+            }
+        }
+        self.rebalance();
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u32) -> Option<&T> {
+        // Safety: occupied slots are initialized
+        self.contains(slot)
+            .then(|| unsafe { self.values.get_unchecked(slot as usize).assume_init_ref() })
+    }
+
+    /// Whether `slot` is occupied
+    pub fn contains(&self, slot: u32) -> bool {
+        slot < self.capacity() && self.occupied.contains(slot)
+    }
+
+    /// Find the slot of an element stored in this slab, from a reference to it
+    ///
+    /// Panics if `element` is not stored in this slab, or if `T` is zero-sized, since such elements
+    /// have no distinct addresses.
+    pub fn key_of(&self, element: &T) -> u32 {
+        assert!(mem::size_of::<T>() != 0, "zero-sized elements have no key");
+        let base = self.values.as_ptr() as usize;
+        let offset = (element as *const T as usize).wrapping_sub(base);
+        let slot = u32::try_from(offset / mem::size_of::<T>()).unwrap_or(NONE);
+        match self.get(slot) {
+            Some(x) if ptr::eq(x, element) => slot,
+            _ => panic!("element not stored in this slab"),
+        }
+    }
+
+    /// Remove every element for which `f` returns `false`
+    ///
+    /// Elements are visited in slot order.
+    pub fn retain(&mut self, mut f: impl FnMut(u32, &mut T) -> bool) {
+        span!("retain", len = self.len);
+        for i in 0..self.occupied.words().len() {
+            for slot in Bits::new(i, self.occupied.words()[i]) {
+                // Safety: occupied slots are initialized
+                let value = unsafe { self.values[slot as usize].assume_init_mut() };
+                if !f(slot, value) {
+                    self.release(slot);
+                }
+            }
+        }
+        self.rebalance();
+    }
+
+    /// Walk the container in slot order
+    pub fn iter_by_key(&self) -> impl DoubleEndedIterator<Item = (u32, &T)> + '_ {
+        // Safety: occupied slots are initialized
+        self.occupied.iter().map(|slot| {
+            (slot, unsafe {
+                self.values[slot as usize].assume_init_ref()
+            })
+        })
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> &T {
+        self.get(slot).expect("accessing empty slot")
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        assert!(self.is_occupied(slot), "accessing empty slot");
+        // Safety: occupied slots are initialized
+        unsafe { self.values[slot as usize].assume_init_mut() }
+    }
+
+    /// Access `slot` without marking it as most recently used or checking that it's occupied
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be occupied, e.g. as witnessed by [`contains`](Self::contains).
+    pub unsafe fn peek_unchecked(&self, slot: u32) -> &T {
+        debug_assert!(self.contains(slot), "accessing empty slot");
+        self.values.get_unchecked(slot as usize).assume_init_ref()
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used or checking that it's
+    /// occupied
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be occupied, e.g. as witnessed by [`contains`](Self::contains).
+    pub unsafe fn peek_unchecked_mut(&mut self, slot: u32) -> &mut T {
+        debug_assert!(self.contains(slot), "accessing empty slot");
+        self.values
+            .get_unchecked_mut(slot as usize)
+            .assume_init_mut()
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely, without checking that it's
+    /// occupied
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be occupied, e.g. as witnessed by [`contains`](Self::contains).
+    pub unsafe fn get_unchecked_mut(&mut self, slot: u32) -> &mut T {
+        self.freshen(slot);
+        self.peek_unchecked_mut(slot)
+    }
+
+    /// Number of slots more recently used than `slot`, if it's occupied
     ///
-    /// The returned slot is marked as the most recently used.
-    pub fn insert(&mut self, value: T) -> u32 {
-        let id = match self.alloc() {
-            Some(id) => id,
-            None => {
-                let len = self.capacity();
-                let cap = 2 * len.max(2);
-                self.slots = self
-                    .slots
-                    .iter_mut()
-                    .map(|x| Slot {
-                        value: x.value.take(),
-                        next: x.next,
-                        prev: x.prev,
-                    })
-                    .chain((len..cap).map(|n| Slot {
-                        value: None,
-                        prev: NONE,
-                        next: if n + 1 == cap { NONE } else { n + 1 },
-                    }))
-                    .collect();
-                self.free = len + 1;
-                len
+    /// The most recently used slot has rank 0. Takes time proportional to the lesser of the rank
+    /// and the number of less recently used slots.
+    pub fn rank_of(&self, slot: u32) -> Option<u32> {
+        if !self.contains(slot) {
+            return None;
+        }
+        // Search from both ends at once
+        let (mut front, mut back) = (self.head, self.tail);
+        for i in 0..self.len {
+            if front == slot {
+                return Some(i);
             }
-        };
-        let idx = id as usize;
-
-        debug_assert!(self.slots[idx].value.is_none(), "corrupt free list");
-        self.slots[idx].value = Some(value);
-        self.link_at_head(id);
-        self.len += 1;
+            if back == slot {
+                return Some(self.len - 1 - i);
+            }
+            front = self.link(front).next;
+            back = self.link(back).prev;
+        }
+        unreachable!("occupied slot missing from LRU list")
+    }
 
-        id
+    /// The most recently used element for which `pred` returns `true`, and its slot
+    ///
+    /// Elements are visited from most to least recently used, without changing their recency.
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<(u32, &T)> {
+        self.iter().find(|&(_, x)| pred(x))
     }
 
-    /// Get the least recently used slot, if any
-    pub fn lru(&self) -> Option<u32> {
-        if self.tail == NONE {
-            debug_assert_eq!(self.head, NONE);
-            None
-        } else {
-            Some(self.tail)
-        }
+    /// The first non-`None` result of `f` on elements from most to least recently used, and the
+    /// slot of the element that produced it
+    pub fn find_map<B>(&self, mut f: impl FnMut(&T) -> Option<B>) -> Option<(u32, B)> {
+        self.iter().find_map(|(slot, x)| Some((slot, f(x)?)))
     }
 
-    /// Remove the element stored in `slot`, returning it
-    pub fn remove(&mut self, slot: u32) -> T {
-        self.unlink(slot);
-        self.slots[slot as usize].next = self.free;
-        self.slots[slot as usize].prev = NONE;
-        self.free = slot;
-        self.len -= 1;
-        self.slots[slot as usize]
-            .value
-            .take()
-            .expect("removing empty slot")
+    /// The slot with `n` slots more recently used than it, if any
+    ///
+    /// Takes time proportional to `n`.
+    pub fn nth_mru(&self, n: u32) -> Option<u32> {
+        self.iter().nth(n as usize).map(|(slot, _)| slot)
     }
 
-    /// Mark `slot` as the most recently used and access it uniquely
-    pub fn get_mut(&mut self, slot: u32) -> &mut T {
-        self.freshen(slot);
-        self.peek_mut(slot)
+    /// The slot with `n` slots less recently used than it, if any
+    ///
+    /// Takes time proportional to `n`. Pins are disregarded.
+    pub fn nth_lru(&self, n: u32) -> Option<u32> {
+        self.iter().nth_back(n as usize).map(|(slot, _)| slot)
     }
 
-    /// Access `slot` without marking it as most recently used
-    pub fn peek(&self, slot: u32) -> &T {
-        self.slots[slot as usize].value.as_ref().unwrap()
+    /// The slot used next less recently than `slot`, if any
+    ///
+    /// Panics if `slot` is vacant.
+    pub fn next_older(&self, slot: u32) -> Option<u32> {
+        assert!(self.contains(slot), "accessing empty slot");
+        let next = self.link(slot).next;
+        (next != NONE).then_some(next)
     }
 
-    /// Access `slot` uniquely without marking it as most recently used
-    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
-        self.slots[slot as usize].value.as_mut().unwrap()
+    /// The slot used next more recently than `slot`, if any
+    ///
+    /// Panics if `slot` is vacant.
+    pub fn next_newer(&self, slot: u32) -> Option<u32> {
+        assert!(self.contains(slot), "accessing empty slot");
+        let prev = self.link(slot).prev;
+        (prev != NONE).then_some(prev)
     }
 
     /// Walk the container from most to least recently used
     pub fn iter(&self) -> Iter<'_, T> {
         let state = IterState::new(self);
         Iter {
-            slots: &self.slots[..],
+            values: &self.values[..],
+            links: &self.links[..],
             state,
         }
     }
@@ -158,72 +1423,455 @@ impl<T> LruSlab<T> {
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         let state = IterState::new(self);
         IterMut {
-            slots: self.slots[..].as_mut_ptr().cast(),
+            values: self.values[..].as_mut_ptr(),
+            links: &self.links[..],
             state,
             _marker: PhantomData,
         }
     }
 
+    /// Unlink `slot` and return it to the freelist, yielding its value
+    ///
+    /// The midpoint must be rebalanced afterwards.
+    fn release(&mut self, slot: u32) -> T {
+        assert!(self.is_occupied(slot), "removing empty slot");
+        // Safety: checked above
+        unsafe { self.release_unchecked(slot) }
+    }
+
+    /// [`release`](Self::release) without checking that `slot` is occupied
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be occupied.
+    unsafe fn release_unchecked(&mut self, slot: u32) -> T {
+        debug_assert!(self.contains(slot), "removing empty slot");
+        let value = self.values.get_unchecked_mut(slot as usize);
+        // Safety: the slot is occupied, and is marked vacant below before anything can panic
+        let value = {
+            let read = value.assume_init_read();
+            #[cfg(feature = "zeroize")]
+            wipe(value);
+            read
+        };
+        self.unlink(slot);
+        let free = self.free;
+        *self.link_mut(slot) = Link {
+            prev: FREE,
+            next: free,
+        };
+        let meta = self.meta_mut(slot);
+        meta.pins = 0;
+        meta.dirty = false;
+        self.occupied.remove(slot);
+        let prev = match self.reuse_lowest {
+            true => self.occupied.last_absent_below(slot),
+            false => None,
+        };
+        match prev {
+            // Keep the free list sorted by linking in after the next lowest vacant slot
+            Some(prev) => {
+                self.link_mut(slot).next = self.link(prev).next;
+                self.link_mut(prev).next = slot;
+            }
+            None => self.free = slot,
+        }
+        self.len -= 1;
+        self.note_occupancy();
+        count!(self, removals);
+        record!(self, Remove, slot);
+        value
+    }
+
+    /// Reallocate to hold `capacity` slots, adding the new ones to the freelist
+    fn grow(&mut self, capacity: u32) {
+        assert!(capacity != u32::MAX, "capacity too large");
+        let additional = capacity.saturating_sub(self.capacity()) as usize;
+        span!(if additional != 0, "grow", from = self.capacity(), to = capacity);
+        #[cfg(feature = "zeroize")]
+        if capacity as usize > self.values.capacity() {
+            let mut values = Vec::new_in(self.values.allocator().clone());
+            values.reserve_exact(capacity as usize);
+            self.move_values(values);
+        }
+        self.values.reserve_exact(additional);
+        self.links.reserve_exact(additional);
+        self.meta.reserve_exact(additional);
+        self.occupied.grow(capacity);
+        self.grow_reserved(capacity);
+    }
+
+    /// Reallocate to hold `capacity` slots as if by [`grow`](Self::grow), failing instead of
+    /// panicking or aborting if storage can't be reserved
+    fn try_grow(&mut self, capacity: u32) -> Result<(), ReserveError> {
+        if capacity == u32::MAX {
+            return Err(ReserveError::CapacityOverflow);
+        }
+        let additional = capacity.saturating_sub(self.capacity()) as usize;
+        span!(if additional != 0, "grow", from = self.capacity(), to = capacity);
+        let failed = |_| ReserveError::AllocFailed;
+        #[cfg(feature = "zeroize")]
+        if capacity as usize > self.values.capacity() {
+            let mut values = Vec::new_in(self.values.allocator().clone());
+            values
+                .try_reserve_exact(capacity as usize)
+                .map_err(failed)?;
+            self.move_values(values);
+        }
+        self.values.try_reserve_exact(additional).map_err(failed)?;
+        self.links.try_reserve_exact(additional).map_err(failed)?;
+        self.meta.try_reserve_exact(additional).map_err(failed)?;
+        self.occupied.try_reserve(capacity).map_err(failed)?;
+        self.grow_reserved(capacity);
+        Ok(())
+    }
+
+    /// Extend to `capacity` slots within storage already reserved for them, adding the new ones
+    /// to the freelist
+    ///
+    /// Never allocates or panics, so that [`try_grow`](Self::try_grow) can't either.
+    fn grow_reserved(&mut self, capacity: u32) {
+        let len = self.capacity();
+        if capacity <= len {
+            return;
+        }
+        let free = self.free;
+        let additional = (capacity - len) as usize;
+        let added = [
+            extend_reserved(
+                &mut self.values,
+                iter::repeat_with(MaybeUninit::uninit).take(additional),
+            ),
+            extend_reserved(
+                &mut self.links,
+                (len..capacity).map(|n| Link {
+                    prev: FREE,
+                    next: if n + 1 == capacity { free } else { n + 1 },
+                }),
+            ),
+            extend_reserved(
+                &mut self.meta,
+                iter::repeat_with(Meta::default).take(additional),
+            ),
+        ];
+        debug_assert_eq!(added, [additional; 3], "storage not reserved");
+        self.occupied.grow_reserved(capacity);
+        self.free = len;
+        if self.reuse_lowest {
+            self.sort_free_list();
+        }
+    }
+
+    /// Move the values into `values`, an empty allocation with room for them all, then wipe the
+    /// old allocation before freeing it
+    ///
+    /// Reallocating through the `Vec` instead would leave a copy of every value in freed memory.
+    #[cfg(feature = "zeroize")]
+    fn move_values(&mut self, mut values: Vec<MaybeUninit<T>, A>) {
+        let len = self.values.len();
+        debug_assert!(values.is_empty() && values.capacity() >= len);
+        // Safety: `values` has room for `len` elements, which needn't be initialized
+        unsafe {
+            ptr::copy_nonoverlapping(self.values.as_ptr(), values.as_mut_ptr(), len);
+            values.set_len(len);
+        }
+        self.values.iter_mut().for_each(wipe);
+        self.values = values;
+    }
+
+    /// Relink the free list in ascending order
+    fn sort_free_list(&mut self) {
+        self.free = NONE;
+        for slot in (0..self.capacity()).rev() {
+            if self.link(slot).prev == FREE {
+                self.link_mut(slot).next = self.free;
+                self.free = slot;
+            }
+        }
+    }
+
     /// Remove a slot from the freelist
     fn alloc(&mut self) -> Option<u32> {
         if self.free == NONE {
             return None;
         }
         let slot = self.free;
-        self.free = self.slots[slot as usize].next;
+        self.free = self.link(slot).next;
         Some(slot)
     }
 
     /// Mark `slot` as the most recently used
+    ///
+    /// `slot` must be occupied.
     fn freshen(&mut self, slot: u32) {
-        if self.slots[slot as usize].prev == NONE {
+        count!(self, hits);
+        record!(self, Touch, slot);
+        if self.link(slot).prev == NONE {
             // This is already the freshest slot, so we don't need to do anything
             debug_assert_eq!(self.head, slot, "corrupt LRU list");
             return;
         }
+        if self.is_fresh(slot) || !self.roll_freshen() {
+            return;
+        }
 
         self.unlink(slot);
         self.link_at_head(slot);
+        self.rebalance();
+    }
+
+    /// Whether accessing `slot` should leave it where it is
+    fn is_fresh(&self, slot: u32) -> bool {
+        if self.head == slot {
+            return true;
+        }
+        // Spare the default configuration a load of the metadata
+        if self.freshen_threshold == 0 && self.epoch.is_none() {
+            return false;
+        }
+        let meta = self.meta(slot);
+        let age = self.clock.wrapping_sub(meta.linked_at);
+        meta.hot
+            && (self.freshen_threshold != 0 && age <= self.freshen_threshold
+                || self.epoch.is_some() && age < self.clock.wrapping_sub(self.epoch_start))
+    }
+
+    /// Decide randomly whether an access should move a slot, per the freshen probability
+    fn roll_freshen(&mut self) -> bool {
+        self.freshen_odds == ALWAYS || self.rng.next_u64() >> 32 < self.freshen_odds
     }
 
     /// Add a link to the head of the list
     fn link_at_head(&mut self, slot: u32) {
-        let idx = slot as usize;
-        if self.head == NONE {
+        let head = self.head;
+        if head == NONE {
             // List was empty
-            self.slots[idx].next = NONE;
             self.tail = slot;
         } else {
-            self.slots[idx].next = self.head;
-            self.slots[self.head as usize].prev = slot;
+            self.link_mut(head).prev = slot;
         }
-        self.slots[idx].prev = NONE;
+        *self.link_mut(slot) = Link {
+            prev: NONE,
+            next: head,
+        };
+        self.clock = self.clock.wrapping_add(1);
+        let clock = self.clock;
+        let meta = self.meta_mut(slot);
+        meta.old = false;
+        meta.linked_at = clock;
+        meta.hot = true;
         self.head = slot;
     }
 
+    /// Add a link to the tail of the list
+    fn link_at_tail(&mut self, slot: u32) {
+        let tail = self.tail;
+        *self.link_mut(slot) = Link {
+            prev: tail,
+            next: NONE,
+        };
+        if tail == NONE {
+            self.head = slot;
+        } else {
+            self.link_mut(tail).next = slot;
+        }
+        self.tail = slot;
+        let track_old = self.track_old;
+        let meta = self.meta_mut(slot);
+        meta.hot = false;
+        if !track_old {
+            return;
+        }
+        // The old sublist is a suffix, so the new tail belongs to it
+        meta.old = true;
+        if self.midpoint == NONE {
+            self.midpoint = slot;
+        }
+        self.old_len += 1;
+    }
+
+    /// Add a link to the head of the old sublist
+    fn link_at_midpoint(&mut self, slot: u32) {
+        let midpoint = self.midpoint;
+        let prev = match midpoint {
+            NONE => self.tail,
+            _ => self.link(midpoint).prev,
+        };
+        *self.link_mut(slot) = Link {
+            prev,
+            next: midpoint,
+        };
+        if prev == NONE {
+            self.head = slot;
+        } else {
+            self.link_mut(prev).next = slot;
+        }
+        if midpoint == NONE {
+            self.tail = slot;
+        } else {
+            self.link_mut(midpoint).prev = slot;
+        }
+        let meta = self.meta_mut(slot);
+        meta.old = true;
+        meta.hot = false;
+        self.midpoint = slot;
+        self.old_len += 1;
+    }
+
+    /// Move the midpoint so that the old sublist holds 3/8ths of the slots, if it's maintained
+    fn rebalance(&mut self) {
+        let target = match self.track_old {
+            true => (u64::from(self.len) * 3 / 8) as u32,
+            false => 0,
+        };
+        while self.old_len < target {
+            self.midpoint = match self.midpoint {
+                NONE => self.tail,
+                midpoint => self.link(midpoint).prev,
+            };
+            self.meta_mut(self.midpoint).old = true;
+            self.old_len += 1;
+        }
+        while self.old_len > target {
+            let midpoint = self.midpoint;
+            self.meta_mut(midpoint).old = false;
+            self.midpoint = self.link(midpoint).next;
+            self.old_len -= 1;
+        }
+        // Every public mutation of the lists ends here
+        #[cfg(feature = "strict")]
+        if let Err(e) = self.check_invariants() {
+            panic!("corrupt LruSlab: {e}");
+        }
+    }
+
+    fn is_occupied(&self, slot: u32) -> bool {
+        self.links[slot as usize].prev != FREE
+    }
+
     /// Remove a link from anywhere in the list
+    ///
+    /// `slot` must be occupied.
     fn unlink(&mut self, slot: u32) {
-        let idx = slot as usize;
-        if self.slots[idx].prev != NONE {
-            self.slots[self.slots[idx].prev as usize].next = self.slots[idx].next;
+        let Link { prev, next } = *self.link(slot);
+        // Spare the metadata unless the old sublist is maintained
+        if self.track_old && self.meta_mut(slot).old {
+            self.meta_mut(slot).old = false;
+            if self.midpoint == slot {
+                self.midpoint = next;
+            }
+            self.old_len -= 1;
+        }
+        if prev != NONE {
+            self.link_mut(prev).next = next;
         } else {
-            self.head = self.slots[idx].next;
+            self.head = next;
         }
-        if self.slots[idx].next != NONE {
-            self.slots[self.slots[idx].next as usize].prev = self.slots[idx].prev;
+        if next != NONE {
+            self.link_mut(next).prev = prev;
         } else {
             // This was the tail
-            self.tail = self.slots[idx].prev;
+            self.tail = prev;
+        }
+    }
+
+    // List operations index through the following accessors, which skip bounds checks. This is
+    // sound because every slot index the slab stores, in `head`, `tail`, `midpoint`, `free`, or
+    // any link, is either `NONE`, `FREE`, or less than the capacity, and the per-slot arrays all
+    // have exactly capacity elements. Slots supplied by callers must be validated before they
+    // reach these.
+
+    fn link(&self, slot: u32) -> &Link {
+        debug_assert!(slot < self.capacity(), "slot out of bounds");
+        // Safety: see above
+        unsafe { self.links.get_unchecked(slot as usize) }
+    }
+
+    fn link_mut(&mut self, slot: u32) -> &mut Link {
+        debug_assert!(slot < self.capacity(), "slot out of bounds");
+        // Safety: see above
+        unsafe { self.links.get_unchecked_mut(slot as usize) }
+    }
+
+    fn meta(&self, slot: u32) -> &Meta {
+        debug_assert!(slot < self.capacity(), "slot out of bounds");
+        // Safety: see above
+        unsafe { self.meta.get_unchecked(slot as usize) }
+    }
+
+    fn meta_mut(&mut self, slot: u32) -> &mut Meta {
+        debug_assert!(slot < self.capacity(), "slot out of bounds");
+        // Safety: see above
+        unsafe { self.meta.get_unchecked_mut(slot as usize) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone, A: Allocator + Clone> Clone for LruSlab<T, A> {
+    fn clone(&self) -> Self {
+        let mut values = Vec::with_capacity_in(self.values.len(), self.values.allocator().clone());
+        values.extend((0..self.capacity()).map(|slot| match self.get(slot) {
+            Some(value) => MaybeUninit::new(value.clone()),
+            None => MaybeUninit::uninit(),
+        }));
+        Self {
+            values,
+            links: self.links.clone(),
+            meta: self.meta.clone(),
+            occupied: self.occupied.clone(),
+            head: self.head,
+            tail: self.tail,
+            free: self.free,
+            len: self.len,
+            peak_len: self.peak_len,
+            track_old: self.track_old,
+            midpoint: self.midpoint,
+            old_len: self.old_len,
+            accesses: self.accesses.clone(),
+            clock: self.clock,
+            freshen_threshold: self.freshen_threshold,
+            freshen_odds: self.freshen_odds,
+            rng: self.rng.clone(),
+            epoch: self.epoch,
+            epoch_start: self.epoch_start,
+            reuse_lowest: self.reuse_lowest,
+            shrink_below: self.shrink_below,
+            shrink_after: self.shrink_after,
+            sparse_ops: self.sparse_ops,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+            #[cfg(feature = "trace")]
+            trace_sink: None,
+            #[cfg(feature = "trace")]
+            trace_time: self.trace_time,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator> Drop for LruSlab<T, A> {
+    fn drop(&mut self) {
+        if !mem::needs_drop::<T>() && !cfg!(feature = "zeroize") {
+            return;
+        }
+        for slot in self.occupied.iter() {
+            // Safety: the slot is occupied, so in bounds, and is never accessed again
+            let value = unsafe { self.values.get_unchecked_mut(slot as usize) };
+            unsafe { value.assume_init_drop() }
+            #[cfg(feature = "zeroize")]
+            wipe(value);
         }
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Default for LruSlab<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> FromIterator<T> for LruSlab<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let iter = iter.into_iter();
@@ -235,7 +1883,50 @@ impl<T> FromIterator<T> for LruSlab<T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a LruSlab<T> {
+#[cfg(feature = "alloc")]
+impl<T> From<alloc::vec::Vec<T>> for LruSlab<T> {
+    /// Store each element in turn, so the `n`th is in slot `n` and the last is the most recently
+    /// used, with exactly enough capacity
+    ///
+    /// Panics if there are too many elements to index; see
+    /// [`try_from_vec`](LruSlab::try_from_vec).
+    fn from(values: alloc::vec::Vec<T>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> From<[T; N]> for LruSlab<T> {
+    /// Store each element in turn, so the `n`th is in slot `n` and the last is the most recently
+    /// used, with exactly enough capacity
+    fn from(values: [T; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Create an [`LruSlab`] holding the given elements, from most to least recently used
+///
+/// As with [`LruSlab::from_ordered_iter`], the `n`th element is stored in slot `n`. The capacity
+/// is exactly the number of elements.
+///
+/// ```
+/// let slab = lru_slab::lru_slab!['a', 'b', 'c'];
+/// assert_eq!(slab.lru(), Some(2));
+/// assert_eq!(slab.capacity(), 3);
+/// ```
+#[macro_export]
+macro_rules! lru_slab {
+    () => {
+        $crate::LruSlab::new()
+    };
+    ($($x:expr),+ $(,)?) => {
+        $crate::LruSlab::from_ordered_iter([$($x),+])
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, A: Allocator + Clone> IntoIterator for &'a LruSlab<T, A> {
     type Item = (u32, &'a T);
 
     type IntoIter = Iter<'a, T>;
@@ -245,7 +1936,8 @@ impl<'a, T> IntoIterator for &'a LruSlab<T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut LruSlab<T> {
+#[cfg(feature = "alloc")]
+impl<'a, T, A: Allocator + Clone> IntoIterator for &'a mut LruSlab<T, A> {
     type Item = (u32, &'a mut T);
 
     type IntoIter = IterMut<'a, T>;
@@ -255,54 +1947,428 @@ impl<'a, T> IntoIterator for &'a mut LruSlab<T> {
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for LruSlab<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_map().entries(self).finish()
+#[cfg(feature = "alloc")]
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for LruSlab<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+/// A summary of the slab's shape, omitting its elements
+#[cfg(all(feature = "alloc", feature = "defmt"))]
+impl<T, A: Allocator + Clone> defmt::Format for LruSlab<T, A> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        let end = |slot| (slot != NONE).then_some(slot);
+        defmt::write!(
+            f,
+            "LruSlab {{ len: {}, capacity: {}, head: {}, tail: {} }}",
+            self.len,
+            self.capacity(),
+            end(self.head),
+            end(self.tail),
+        );
+    }
+}
+
+/// An inconsistency in an [`LruSlab`]'s bookkeeping, found by [`LruSlab::check_invariants`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvariantError {
+    /// Slot at which the inconsistency was found, if it concerns a particular slot
+    pub slot: Option<u32>,
+    /// The invariant that was violated
+    pub reason: &'static str,
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.slot {
+            Some(slot) => write!(f, "{} at slot {}", self.reason, slot),
+            None => f.write_str(self.reason),
+        }
+    }
+}
+
+impl core::error::Error for InvariantError {}
+
+/// Reasons that storage for more elements couldn't be reserved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReserveError {
+    /// More slots would be needed than can be addressed by a `u32`
+    CapacityOverflow,
+    /// The allocator couldn't provide the memory
+    AllocFailed,
+}
+
+impl fmt::Display for ReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CapacityOverflow => f.write_str("capacity overflow"),
+            Self::AllocFailed => f.write_str("memory allocation failed"),
+        }
+    }
+}
+
+impl core::error::Error for ReserveError {}
+
+/// A failure to [`try_insert`](LruSlab::try_insert) a value, which is returned
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InsertError<T> {
+    /// The value that couldn't be inserted
+    pub value: T,
+    /// Why storage couldn't be reserved for it
+    pub reason: ReserveError,
+}
+
+impl<T> fmt::Debug for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InsertError")
+            .field("reason", &self.reason)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "insertion failed: {}", self.reason)
+    }
+}
+
+impl<T> core::error::Error for InsertError<T> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.reason)
+    }
+}
+
+/// An attempt to access a vacant slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VacantError {
+    /// The slot that was vacant
+    pub slot: u32,
+}
+
+impl fmt::Display for VacantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "slot {} is vacant", self.slot)
+    }
+}
+
+impl core::error::Error for VacantError {}
+
+#[cfg(feature = "trace")]
+type TraceSink = alloc::boxed::Box<dyn FnMut(TraceEvent) + Send + Sync>;
+
+#[cfg(feature = "alloc")]
+/// Bookkeeping for a slot that's only needed off the hottest paths
+#[derive(Clone, Default)]
+struct Meta {
+    /// Whether the slot is in the old sublist
+    old: bool,
+    /// Number of outstanding pins
+    pins: u32,
+    /// Value of `clock` when the slot was last inserted or linked at the head
+    linked_at: u32,
+    /// Whether the slot has stayed in order since it was last linked at the head
+    hot: bool,
+    /// Whether the slot was marked dirty since it was last flushed
+    dirty: bool,
+}
+
+#[cfg(feature = "alloc")]
+/// Append `items` to `vec` within its spare capacity, returning how many fit
+///
+/// Unlike `Vec::extend`, never allocates or panics.
+pub(crate) fn extend_reserved<T, A: Allocator>(
+    vec: &mut Vec<T, A>,
+    items: impl Iterator<Item = T>,
+) -> usize {
+    let mut added = 0;
+    for (spare, item) in vec.spare_capacity_mut().iter_mut().zip(items) {
+        spare.write(item);
+        added += 1;
+    }
+    // Safety: the first `added` spare elements were just initialized
+    unsafe { vec.set_len(vec.len() + added) };
+    added
+}
+
+/// Overwrite the bytes of a vacated value, so that no copy of it lingers in memory
+///
+/// Heap storage owned by the value is the value's own responsibility, e.g. via
+/// `zeroize::Zeroizing`.
+#[cfg(feature = "zeroize")]
+fn wipe<T>(value: &mut MaybeUninit<T>) {
+    // Safety: any bytes are a valid `MaybeUninit`
+    unsafe { zeroize::zeroize_flat_type(value) }
+}
+
+#[cfg(feature = "alloc")]
+/// Increment a counter in the [`Stats`] of an [`LruSlab`], if the `stats` feature is enabled
+macro_rules! count {
+    ($slab:expr, $counter:ident) => {
+        #[cfg(feature = "stats")]
+        {
+            $slab.stats.$counter += 1;
+        }
+    };
+}
+#[cfg(feature = "alloc")]
+use count;
+
+#[cfg(feature = "alloc")]
+/// Report an access to the trace sink of an [`LruSlab`], if the `trace` feature is enabled
+macro_rules! record {
+    ($slab:expr, $kind:ident, $slot:expr) => {
+        #[cfg(feature = "trace")]
+        $slab.record(TraceKind::$kind, $slot);
+    };
+}
+#[cfg(feature = "alloc")]
+use record;
+
+#[cfg(feature = "alloc")]
+/// Enter a `tracing` span for the rest of the enclosing block, if the `tracing` feature is enabled
+///
+/// A leading `if` condition enters the span only when it holds.
+macro_rules! span {
+    ($name:literal $(, $field:ident $(= $value:expr)?)*) => {
+        span!(if true, $name $(, $field $(= $value)?)*)
+    };
+    (if $cond:expr, $name:literal $(, $field:ident $(= $value:expr)?)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = ($cond).then(|| {
+            tracing::debug_span!($name $(, $field $(= $value)?)*).entered()
+        });
+    };
+}
+#[cfg(feature = "alloc")]
+use span;
+
+/// Number of elements beyond which a batch operation is traced
+#[cfg(feature = "tracing")]
+const LARGE_BATCH: usize = 1024;
+
+const NONE: u32 = u32::MAX;
+
+#[cfg(feature = "alloc")]
+/// Freshen odds under which every access moves the slot
+const ALWAYS: u64 = 1 << 32;
+
+/// `Link::prev` of a vacant slot; never a valid index, since capacity is less than `u32::MAX`
+const FREE: u32 = u32::MAX - 1;
+
+#[cfg(feature = "alloc")]
+/// Number of accesses buffered by [`LruSlab::record_access`] before they're applied
+const ACCESS_BUFFER: usize = 64;
+
+#[cfg(feature = "alloc")]
+/// Unique access to an element of an [`LruSlab`] that's removed on drop, returned by
+/// [`LruSlab::take_guard`]
+pub struct TakeGuard<'a, T, A: Allocator + Clone = Global> {
+    slab: &'a mut LruSlab<T, A>,
+    slot: u32,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, A: Allocator + Clone> TakeGuard<'a, T, A> {
+    /// The slot of the guarded element
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+
+    /// Leave the element in the slab, returning unique access to it
+    pub fn keep(self) -> &'a mut T {
+        let this = mem::ManuallyDrop::new(self);
+        // Safety: `this` is never used or dropped again
+        let slab = unsafe { ptr::read(&this.slab) };
+        slab.peek_mut(this.slot)
+    }
+
+    /// Remove the element now, returning it
+    pub fn take(self) -> T {
+        let this = mem::ManuallyDrop::new(self);
+        // Safety: `this` is never used or dropped again
+        let slab = unsafe { ptr::read(&this.slab) };
+        slab.remove(this.slot)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator + Clone> Deref for TakeGuard<'_, T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.slab.peek(self.slot)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator + Clone> DerefMut for TakeGuard<'_, T, A> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.slab.peek_mut(self.slot)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator + Clone> Drop for TakeGuard<'_, T, A> {
+    fn drop(&mut self) {
+        self.slab.remove(self.slot);
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// A handle to a vacant slot in an [`LruSlab`], returned by [`LruSlab::vacant_entry`]
+pub struct VacantEntry<'a, T, A: Allocator = Global> {
+    slab: &'a mut LruSlab<T, A>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, A: Allocator + Clone> VacantEntry<'a, T, A> {
+    /// The slot a value inserted through this entry will be stored in
+    pub fn key(&self) -> u32 {
+        self.slab.vacant_key()
+    }
+
+    /// Store `value` in the slot, marking it as the most recently used
+    pub fn insert(self, value: T) -> &'a mut T {
+        let slot = self.slab.insert(value);
+        self.slab.peek_mut(slot)
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Iterator that removes elements from an [`LruSlab`], returned by [`LruSlab::evict_n`]
+pub struct EvictN<'a, T, A: Allocator = Global> {
+    slab: &'a mut LruSlab<T, A>,
+    remaining: u32,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator + Clone> Iterator for EvictN<'_, T, A> {
+    type Item = (u32, T);
+    fn next(&mut self) -> Option<(u32, T)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.slab.pop_lru()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = self.remaining.min(self.slab.len);
+        (0, Some(upper as usize))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator + Clone> FusedIterator for EvictN<'_, T, A> {}
+
+#[cfg(feature = "alloc")]
+/// Iterator that removes elements from an [`LruSlab`], returned by [`LruSlab::evict_while`]
+pub struct EvictWhile<'a, T, F, A: Allocator = Global> {
+    slab: &'a mut LruSlab<T, A>,
+    pred: F,
+    done: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F, A: Allocator + Clone> Iterator for EvictWhile<'_, T, F, A>
+where
+    F: FnMut(u32, &T) -> bool,
+{
+    type Item = (u32, T);
+    fn next(&mut self) -> Option<(u32, T)> {
+        if self.done {
+            return None;
+        }
+        let slot = self.slab.lru();
+        match slot {
+            Some(slot) if (self.pred)(slot, self.slab.peek(slot)) => {
+                count!(self.slab, evictions);
+                Some((slot, self.slab.remove(slot)))
+            }
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = if self.done { 0 } else { self.slab.len };
+        (0, Some(upper as usize))
     }
 }
 
-#[derive(Clone)]
-struct Slot<T> {
-    value: Option<T>,
-    /// Next slot in the LRU or free list
-    next: u32,
-    /// Previous slot in the LRU list; NONE when free
-    prev: u32,
+#[cfg(feature = "alloc")]
+impl<T, F, A: Allocator + Clone> FusedIterator for EvictWhile<'_, T, F, A> where
+    F: FnMut(u32, &T) -> bool
+{
 }
 
-const NONE: u32 = u32::MAX;
-
 /// Iterator over elements of an [`LruSlab`], from most to least recently used
 pub struct Iter<'a, T> {
-    slots: &'a [Slot<T>],
+    values: &'a [MaybeUninit<T>],
+    links: &'a [Link],
     state: IterState,
 }
 
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values,
+            links: self.links,
+            state: self.state.clone(),
+        }
+    }
+}
+
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = (u32, &'a T);
     fn next(&mut self) -> Option<(u32, &'a T)> {
-        let idx = self.state.next(|i| self.slots[i as usize].next)?;
-        let result = self.slots[idx as usize]
-            .value
-            .as_ref()
-            .expect("corrupt LRU list");
+        // Safety: slots in the LRU list are occupied, and hence in bounds
+        let idx = self
+            .state
+            .next(|i| unsafe { self.links.get_unchecked(i as usize) }.next)?;
+        let result = unsafe { self.values.get_unchecked(idx as usize).assume_init_ref() };
         Some((idx, result))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.state.len as usize, Some(self.state.len as usize))
     }
+
+    fn nth(&mut self, n: usize) -> Option<(u32, &'a T)> {
+        self.state.skip(n, |i| self.links[i as usize].next);
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.state.len as usize
+    }
+
+    fn last(mut self) -> Option<(u32, &'a T)> {
+        self.next_back()
+    }
 }
 
 impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
     fn next_back(&mut self) -> Option<(u32, &'a T)> {
-        let idx = self.state.next_back(|i| self.slots[i as usize].prev)?;
-        let result = self.slots[idx as usize]
-            .value
-            .as_ref()
-            .expect("corrupt LRU list");
+        // Safety: slots in the LRU list are occupied, and hence in bounds
+        let idx = self
+            .state
+            .next_back(|i| unsafe { self.links.get_unchecked(i as usize) }.prev)?;
+        let result = unsafe { self.values.get_unchecked(idx as usize).assume_init_ref() };
         Some((idx, result))
     }
+
+    fn nth_back(&mut self, n: usize) -> Option<(u32, &'a T)> {
+        self.state.skip_back(n, |i| self.links[i as usize].prev);
+        self.next_back()
+    }
 }
 
 impl<T> ExactSizeIterator for Iter<'_, T> {
@@ -313,58 +2379,69 @@ impl<T> ExactSizeIterator for Iter<'_, T> {
 
 impl<T> FusedIterator for Iter<'_, T> {}
 
+#[cfg(feature = "alloc")]
 /// Iterator over mutable elements of an [`LruSlab`], from most to least recently used
 pub struct IterMut<'a, T> {
-    slots: *mut Slot<T>,
+    values: *mut MaybeUninit<T>,
+    links: &'a [Link],
     state: IterState,
-    _marker: PhantomData<&'a mut [Slot<T>]>,
+    _marker: PhantomData<&'a mut [MaybeUninit<T>]>,
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = (u32, &'a mut T);
     fn next(&mut self) -> Option<(u32, &'a mut T)> {
-        // Safety: `next` returns unique in-bounds indices, and no live references overlap with any
-        // `next` field
-        unsafe {
-            let idx = self
-                .state
-                .next(|i| *addr_of_mut!((*self.slots.add(i as usize)).next))?;
-            let result = (*addr_of_mut!((*self.slots.add(idx as usize)).value))
-                .as_mut()
-                .expect("corrupt LRU list");
-            Some((idx, result))
-        }
+        let idx = self.state.next(|i| self.links[i as usize].next)?;
+        // Safety: `next` returns unique in-bounds indices of occupied slots
+        let result = unsafe { (*self.values.add(idx as usize)).assume_init_mut() };
+        Some((idx, result))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.state.len as usize, Some(self.state.len as usize))
     }
+
+    fn nth(&mut self, n: usize) -> Option<(u32, &'a mut T)> {
+        self.state.skip(n, |i| self.links[i as usize].next);
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.state.len as usize
+    }
+
+    fn last(mut self) -> Option<(u32, &'a mut T)> {
+        self.next_back()
+    }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
     fn next_back(&mut self) -> Option<(u32, &'a mut T)> {
-        // Safety: `next_back` returns unique in-bounds indices, and no live references overlap with
-        // any `prev` field
-        unsafe {
-            let idx = self
-                .state
-                .next_back(|i| *addr_of_mut!((*self.slots.add(i as usize)).prev))?;
-            let result = (*addr_of_mut!((*self.slots.add(idx as usize)).value))
-                .as_mut()
-                .expect("corrupt LRU list");
-            Some((idx, result))
-        }
+        let idx = self.state.next_back(|i| self.links[i as usize].prev)?;
+        // Safety: `next_back` returns unique in-bounds indices of occupied slots
+        let result = unsafe { (*self.values.add(idx as usize)).assume_init_mut() };
+        Some((idx, result))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<(u32, &'a mut T)> {
+        self.state.skip_back(n, |i| self.links[i as usize].prev);
+        self.next_back()
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> ExactSizeIterator for IterMut<'_, T> {
     fn len(&self) -> usize {
         self.state.len as usize
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> FusedIterator for IterMut<'_, T> {}
 
+#[derive(Clone)]
 struct IterState {
     head: u32,
     tail: u32,
@@ -372,7 +2449,8 @@ struct IterState {
 }
 
 impl IterState {
-    fn new<T>(slab: &LruSlab<T>) -> Self {
+    #[cfg(feature = "alloc")]
+    fn new<T, A: Allocator>(slab: &LruSlab<T, A>) -> Self {
         Self {
             head: slab.head,
             tail: slab.tail,
@@ -399,11 +2477,29 @@ impl IterState {
         self.len -= 1;
         Some(idx)
     }
+
+    /// Advance past `n` slots from the front, without visiting their values
+    fn skip(&mut self, n: usize, get_next: impl Fn(u32) -> u32) {
+        let n = n.min(self.len as usize) as u32;
+        for _ in 0..n {
+            self.head = get_next(self.head);
+        }
+        self.len -= n;
+    }
+
+    /// Advance past `n` slots from the back, without visiting their values
+    fn skip_back(&mut self, n: usize, get_prev: impl Fn(u32) -> u32) {
+        let n = n.min(self.len as usize) as u32;
+        for _ in 0..n {
+            self.tail = get_prev(self.tail);
+        }
+        self.len -= n;
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
-    use alloc::{format, string::String, vec::Vec};
+    use alloc::{boxed::Box, format, string::String, vec::Vec};
 
     use super::*;
 
@@ -458,6 +2554,324 @@ mod tests {
         assert_eq!(slab.iter().collect::<Vec<_>>(), double_reversed);
     }
 
+    #[test]
+    fn insert_cold() {
+        let mut slab = (0..8).collect::<LruSlab<_>>();
+        // The old sublist isn't maintained until it's needed
+        assert!(slab.meta.iter().all(|meta| !meta.old));
+        for x in 8..100 {
+            slab.insert_cold(x);
+            // Entries from the scan are evicted before the most recently used 5/8ths
+            let evicted = slab.remove(slab.lru().unwrap());
+            assert!(!(3..8).contains(&evicted));
+        }
+        let x = slab.insert_cold(100);
+        slab.get_mut(x);
+        assert_eq!(
+            slab.iter().map(|(_, &x)| x).collect::<Vec<_>>(),
+            [100, 7, 6, 5, 4, 3, 99, 98, 97]
+        );
+    }
+
+    #[test]
+    fn insert_at_tail() {
+        let mut slab = LruSlab::new();
+        slab.insert('a');
+        let b = slab.insert_at_tail('b');
+        slab.insert('c');
+        assert_eq!(slab.lru(), Some(b));
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "cab");
+    }
+
+    #[test]
+    fn pin() {
+        let mut slab = LruSlab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        slab.pin(a);
+        slab.pin(a);
+        assert_eq!(slab.lru(), Some(b));
+        slab.unpin(a);
+        assert_eq!(slab.pop_lru(), Some((b, 'b')));
+        assert_eq!(slab.pop_lru(), None);
+        slab.unpin(a);
+        assert_eq!(slab.pop_lru(), Some((a, 'a')));
+    }
+
+    #[test]
+    fn insert_many() {
+        let mut slab = LruSlab::new();
+        let a = slab.insert('a');
+        let mut slots = Vec::new();
+        slab.insert_many("bcd".chars(), &mut slots);
+        assert_eq!(slots.len(), 3);
+        assert_eq!(slab.capacity(), 4);
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "dcba");
+        assert_eq!(*slab.peek(slots[0]), 'b');
+        assert_eq!(slab.lru(), Some(a));
+        slab.insert_many(['e'], &mut slots);
+        assert_eq!(slab.capacity(), 8);
+    }
+
+    #[test]
+    fn remove_many() {
+        let mut slab = "abcde".chars().collect::<LruSlab<_>>();
+        let mut removed = String::new();
+        slab.remove_many(&[3, 0, 1], |_, x| removed.push(x));
+        assert_eq!(removed, "dab");
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "ec");
+        assert_eq!(slab.vacant_key(), 1);
+    }
+
+    #[test]
+    fn touch_many() {
+        let mut slab = "abcde".chars().collect::<LruSlab<_>>();
+        slab.touch_many(&[1, 3, 0]);
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "bdaec");
+    }
+
+    #[test]
+    fn evict_n() {
+        let mut slab = "abcd".chars().collect::<LruSlab<_>>();
+        slab.pin(1);
+        let evicted = slab.evict_n(2).map(|(_, x)| x).collect::<String>();
+        assert_eq!(evicted, "ac");
+        assert_eq!(slab.evict_n(5).count(), 1);
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "b");
+    }
+
+    #[test]
+    fn evict_while() {
+        let mut slab = [1, 2, 3, 4].into_iter().collect::<LruSlab<u32>>();
+        let mut freed = 0;
+        let evicted = slab
+            .evict_while(|_, &x| {
+                freed += x;
+                freed <= 3
+            })
+            .map(|(_, x)| x)
+            .collect::<Vec<_>>();
+        assert_eq!(evicted, [1, 2]);
+        assert_eq!(slab.len(), 2);
+    }
+
+    #[test]
+    fn truncate_to() {
+        let mut slab = "abcd".chars().collect::<LruSlab<_>>();
+        slab.truncate_to(5);
+        assert_eq!(slab.len(), 4);
+        slab.get_mut(0);
+        slab.truncate_to(2);
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "ad");
+        slab.truncate_to(0);
+        assert!(slab.is_empty());
+    }
+
+    #[test]
+    fn split_off_lru() {
+        let mut slab = "abcde".chars().collect::<LruSlab<_>>();
+        slab.get_mut(0);
+        let cold = slab.split_off_lru(3);
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "ae");
+        assert_eq!(cold.iter().map(|(_, x)| x).collect::<String>(), "dcb");
+        assert_eq!(cold.capacity(), 3);
+    }
+
+    #[test]
+    fn partition() {
+        let mut slab = "aBcDe".chars().collect::<LruSlab<_>>();
+        slab.get_mut(1);
+        slab.pin(3);
+        let (upper, lower) = slab.partition(|_, x| x.is_uppercase());
+        assert_eq!(upper.iter().map(|(_, x)| x).collect::<String>(), "BD");
+        assert_eq!(lower.iter().map(|(_, x)| x).collect::<String>(), "eca");
+        assert_eq!(upper.lru(), Some(0));
+    }
+
+    #[test]
+    fn append() {
+        let mut slab = ['a', 'b'].into_iter().collect::<LruSlab<_>>();
+        let mut other = ['c', 'd', 'e'].into_iter().collect::<LruSlab<_>>();
+        other.pin(1);
+        let mut moved = Vec::new();
+        slab.append(&mut other, |old, new| moved.push((old, new)));
+        assert!(other.is_empty());
+        assert_eq!(moved, [(2, 2), (1, 3), (0, 4)]);
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "baedc");
+        assert!(slab.is_pinned(3));
+    }
+
+    #[test]
+    fn slab_compat() {
+        let mut slab = LruSlab::new();
+        let entry = slab.vacant_entry();
+        let key = entry.key();
+        assert_eq!(*entry.insert(key), key);
+        for _ in 0..4 {
+            let entry = slab.vacant_entry();
+            let key = entry.key();
+            entry.insert(key);
+        }
+        assert_eq!(slab.key_of(slab.peek(3)), 3);
+        assert_eq!(slab.get(5), None);
+        slab.retain(|slot, _| slot % 2 == 0);
+        assert_eq!(
+            slab.iter_by_key().map(|(_, &x)| x).collect::<Vec<_>>(),
+            [0, 2, 4]
+        );
+        assert!(!slab.contains(1));
+    }
+
+    #[test]
+    fn freshen_threshold() {
+        let mut slab = (0..4).collect::<LruSlab<_>>();
+        slab.set_freshen_threshold(1);
+        slab.get_mut(2);
+        assert!(slab.iter().map(|(s, _)| s).eq([3, 2, 1, 0]));
+        slab.get_mut(1);
+        assert!(slab.iter().map(|(s, _)| s).eq([1, 3, 2, 0]));
+    }
+
+    #[test]
+    fn freshen_probability() {
+        let mut slab = (0..2).collect::<LruSlab<_>>();
+        slab.set_freshen_probability(0.0);
+        slab.get_mut(0);
+        assert_eq!(slab.lru(), Some(0));
+        slab.set_freshen_probability(0.5);
+        let moves = (0..1000)
+            .filter(|_| {
+                let lru = slab.lru().unwrap();
+                slab.get_mut(lru);
+                slab.lru() != Some(lru)
+            })
+            .count();
+        assert!((400..600).contains(&moves));
+    }
+
+    #[test]
+    fn epochs() {
+        let mut slab = (0..3).collect::<LruSlab<_>>();
+        slab.advance_epoch();
+        slab.get_mut(0);
+        slab.get_mut(1);
+        slab.get_mut(0);
+        assert!(slab.iter().map(|(s, _)| s).eq([1, 0, 2]));
+        slab.advance_epoch();
+        slab.get_mut(0);
+        assert!(slab.iter().map(|(s, _)| s).eq([0, 1, 2]));
+        // Slots that haven't been at the head this epoch still move on access
+        let cold = slab.insert_at_tail(3);
+        slab.get_mut(cold);
+        assert_eq!(slab.iter().next(), Some((cold, &3)));
+    }
+
+    #[test]
+    fn dirty() {
+        let mut slab = (0..4).collect::<LruSlab<_>>();
+        slab.mark_dirty(2);
+        slab.mark_dirty(0);
+        slab.mark_dirty(3);
+        slab.remove(3);
+        assert!(slab.iter_dirty().eq([(0, &0), (2, &2)]));
+        let mut written = Vec::new();
+        slab.flush_dirty(|slot, _| written.push(slot));
+        assert_eq!(written, [0, 2]);
+        assert!(!slab.is_dirty(0));
+        assert_eq!(slab.iter_dirty().count(), 0);
+    }
+
+    #[test]
+    fn reuse_lowest() {
+        let mut slab = (0..200).collect::<LruSlab<_>>();
+        for slot in [150, 3, 70, 199] {
+            slab.remove(slot);
+        }
+        slab.set_reuse_lowest(true);
+        for slot in [5, 130] {
+            slab.remove(slot);
+        }
+        let slots = (0..6).map(|_| slab.insert(0)).collect::<Vec<_>>();
+        assert_eq!(slots, [3, 5, 70, 130, 150, 199]);
+        slab.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn update() {
+        let mut slab = [1, 2].into_iter().collect::<LruSlab<_>>();
+        slab.update(0, |x| {
+            *x += 10;
+            false
+        });
+        assert_eq!(slab.lru(), Some(0));
+        slab.update(0, |_| true);
+        assert!(slab.iter().eq([(0, &11), (1, &2)]));
+    }
+
+    #[test]
+    fn record_access() {
+        let mut slab = "abcd".chars().collect::<LruSlab<_>>();
+        slab.record_access(0);
+        slab.record_access(2);
+        slab.record_access(3);
+        slab.remove(3);
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "cba");
+        slab.flush();
+        assert_eq!(slab.iter().map(|(_, x)| x).collect::<String>(), "cab");
+    }
+
+    #[test]
+    fn maintain() {
+        let mut slab = (0..4).collect::<LruSlab<_>>();
+        for slot in 0..3 {
+            slab.record_access(slot);
+        }
+        assert!(slab.maintain(2));
+        assert!(slab.iter().map(|(s, _)| s).eq([1, 0, 3, 2]));
+        assert!(!slab.maintain(2));
+        assert!(slab.iter().map(|(s, _)| s).eq([2, 1, 0, 3]));
+    }
+
+    #[test]
+    fn shrink() {
+        let mut slab = (0..100).collect::<LruSlab<_>>();
+        slab.set_auto_shrink(0.5, 10);
+        for slot in (20..100).rev() {
+            slab.remove(slot);
+        }
+        slab.remove(5);
+        slab.record_access(0);
+        assert!(slab.maintain(1));
+        assert_eq!(slab.capacity(), 100);
+        assert!(!slab.maintain(1));
+        assert_eq!(slab.capacity(), 20);
+        assert!(slab.vacant_slots().eq([5]));
+        slab.check_invariants().unwrap();
+        slab.insert(0);
+        assert_eq!(slab.insert(0), 20);
+    }
+
+    #[test]
+    fn insert_and_get_mut() {
+        let mut slab = LruSlab::new();
+        slab.insert(0);
+        let (slot, value) = slab.insert_and_get_mut(1);
+        *value += 1;
+        assert_eq!(slab.iter().next(), Some((slot, &2)));
+    }
+
+    #[test]
+    fn take_guard() {
+        let mut slab = (0..3).collect::<LruSlab<_>>();
+        *slab.take_guard(0) += 10;
+        assert!(!slab.contains(0));
+        let mut guard = slab.take_guard(1);
+        *guard += 10;
+        assert_eq!(*guard.keep(), 11);
+        assert_eq!(slab.take_guard(2).take(), 2);
+        assert!(slab.iter().eq([(1, &11)]));
+    }
+
     #[test]
     fn vacant_key() {
         let mut slab = LruSlab::new();
@@ -467,4 +2881,444 @@ mod tests {
         slab.remove(0);
         assert_eq!(slab.vacant_key(), 0);
     }
+
+    #[test]
+    fn max_occupied_slot() {
+        let mut slab = (0..130).collect::<LruSlab<_>>();
+        assert_eq!(slab.max_occupied_slot(), Some(129));
+        for slot in 2..130 {
+            slab.remove(slot);
+        }
+        assert_eq!(slab.max_occupied_slot(), Some(1));
+        slab.clear();
+        assert_eq!(slab.max_occupied_slot(), None);
+    }
+
+    #[test]
+    fn peak_len() {
+        let mut slab = (0..3).collect::<LruSlab<_>>();
+        slab.remove(0);
+        slab.remove(1);
+        assert_eq!(slab.peak_len(), 3);
+        slab.reset_peak_len();
+        assert_eq!(slab.peak_len(), 1);
+        slab.insert(3);
+        assert_eq!(slab.peak_len(), 2);
+    }
+
+    #[test]
+    fn age_percentiles() {
+        let mut slab = (0..10).collect::<LruSlab<_>>();
+        assert_eq!(slab.age_percentiles([50.0, 90.0, 100.0]), Some([4, 8, 9]));
+        slab.get_mut(0);
+        assert_eq!(slab.age_percentiles([0.0, 100.0]), Some([0, 9]));
+        assert_eq!(LruSlab::<()>::new().age_percentiles([50.0]), None);
+    }
+
+    #[test]
+    fn vacant_slots() {
+        let mut slab = (0..4).collect::<LruSlab<_>>();
+        slab.remove(1);
+        slab.remove(3);
+        assert!(slab.vacant_slots().eq([3, 1]));
+        assert_eq!(slab.free_len(), 2);
+        slab.clear();
+        assert_eq!(slab.vacant_slots().count(), 4);
+    }
+
+    #[test]
+    fn drops_values() {
+        let value = alloc::rc::Rc::new(());
+        let mut slab = LruSlab::with_capacity(1);
+        let a = slab.insert(value.clone());
+        // Grows, moving the first value
+        slab.insert(value.clone());
+        drop(slab.remove(a));
+        let copy = slab.clone();
+        assert_eq!(alloc::rc::Rc::strong_count(&value), 3);
+        drop(slab);
+        drop(copy);
+        assert_eq!(alloc::rc::Rc::strong_count(&value), 1);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn wipes_vacated_slots() {
+        let mut slab = LruSlab::new();
+        let a = slab.insert([0xAAu8; 16]);
+        let b = slab.insert([0xBB; 16]);
+        slab.remove(a);
+        slab.clear();
+        for slot in [a, b] {
+            // Safety: zeroed bytes are initialized
+            let bytes = unsafe { slab.values[slot as usize].assume_init_ref() };
+            assert_eq!(bytes, &[0; 16]);
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn wipes_reallocated_values() {
+        use allocator_api2::alloc::AllocError;
+        use core::{alloc::Layout, cell::Cell, ptr::NonNull};
+
+        /// Flags any freed block that still holds part of a value
+        #[derive(Clone, Copy)]
+        struct Checking<'a>(&'a Cell<bool>);
+
+        unsafe impl Allocator for Checking<'_> {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                let bytes = core::slice::from_raw_parts(ptr.as_ptr(), layout.size());
+                if bytes.windows(8).any(|w| w == [0xA5; 8]) {
+                    self.0.set(true);
+                }
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let leaked = Cell::new(false);
+        let mut slab = LruSlab::new_in(Checking(&leaked));
+        let slots = (0..20)
+            .map(|_| slab.insert([0xA5u8; 16]))
+            .collect::<Vec<_>>();
+        for &slot in &slots[10..] {
+            slab.remove(slot);
+        }
+        slab.shrink_to_fit();
+        slab.try_reserve(100).unwrap();
+        slab.insert([0xA5; 16]);
+        drop(slab);
+        assert!(!leaked.get());
+    }
+
+    #[test]
+    fn allocated_bytes() {
+        let slab = LruSlab::<u64>::with_capacity(100);
+        assert!(slab.allocated_bytes() >= 100 * (8 + mem::size_of::<Link>()));
+        assert_eq!(LruSlab::<u64>::new().allocated_bytes(), 0);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn stats() {
+        let mut slab = LruSlab::with_capacity(1);
+        let a = slab.insert('a');
+        slab.insert('b');
+        slab.get_mut(a);
+        slab.touch_many(&[a]);
+        slab.pop_lru();
+        slab.remove(a);
+        let expected = Stats {
+            hits: 2,
+            inserts: 2,
+            removals: 2,
+            evictions: 1,
+            grows: 1,
+        };
+        assert_eq!(slab.stats(), expected);
+        slab.reset_stats();
+        assert_eq!(slab.stats(), Stats::default());
+    }
+
+    #[cfg(all(feature = "trace", feature = "std"))]
+    #[test]
+    fn trace_sink() {
+        use std::sync::{Arc, Mutex};
+        use trace::TraceKind::*;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut slab = LruSlab::new();
+        slab.set_trace_sink({
+            let log = log.clone();
+            move |e| log.lock().unwrap().push(e)
+        });
+        let a = slab.insert(());
+        let b = slab.insert(());
+        slab.get_mut(a);
+        slab.remove(a);
+        slab.clear_trace_sink();
+        slab.insert(());
+        let log = log.lock().unwrap();
+        let events = log.iter().map(|e| (e.time, e.slot, e.kind));
+        assert!(events.eq([
+            (0, a, Insert),
+            (1, b, Insert),
+            (2, a, Touch),
+            (3, a, Remove)
+        ]));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn traces_growth() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+        use tracing::{span, subscriber::Subscriber, Event, Metadata};
+
+        /// Counts spans created
+        #[derive(Default)]
+        struct Spans(AtomicU64);
+
+        impl Subscriber for Spans {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                assert_eq!(span.metadata().name(), "grow");
+                span::Id::from_u64(self.0.fetch_add(1, Ordering::Relaxed) + 1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let spans = alloc::sync::Arc::new(Spans::default());
+        tracing::subscriber::with_default(spans.clone(), || {
+            let mut slab = LruSlab::with_capacity(1);
+            slab.insert(());
+            slab.touch_many(&[0]);
+            slab.insert(());
+        });
+        // Small batches aren't traced
+        assert_eq!(spans.0.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn invariants() {
+        let mut slab = (0..10).collect::<LruSlab<_>>();
+        slab.remove(3);
+        slab.get_mut(5);
+        assert_eq!(slab.check_invariants(), Ok(()));
+        slab.links[5].next = 5;
+        assert_eq!(
+            format!("{}", slab.check_invariants().unwrap_err()),
+            "slot linked more than once at slot 5"
+        );
+    }
+
+    #[test]
+    fn dot() {
+        let mut slab = LruSlab::with_capacity(2);
+        let a = slab.insert(());
+        slab.remove(a);
+        slab.insert(());
+        let mut dot = String::new();
+        slab.write_dot(&mut dot).unwrap();
+        assert_eq!(
+            dot,
+            "digraph lru_slab {
+    rankdir=LR;
+    node [shape=box];
+    0 [label=\"0 (occupied)\", style=solid];
+    1 [label=\"1 (vacant)\", style=dotted];
+    head [shape=plaintext];
+    head -> 0 [style=solid];
+    tail [shape=plaintext];
+    tail -> 0 [style=solid];
+    free [shape=plaintext];
+    free -> 1 [style=dashed];
+}
+"
+        );
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    #[should_panic(expected = "corrupt LruSlab: vacant slot in LRU list at slot 0")]
+    fn strict() {
+        let mut slab = LruSlab::new();
+        let a = slab.insert(());
+        slab.insert(());
+        slab.links[a as usize].prev = FREE;
+        slab.insert(());
+    }
+
+    #[test]
+    fn rank_of() {
+        let mut slab = (0..5).collect::<LruSlab<_>>();
+        slab.get_mut(1);
+        let ranks = (0..6).map(|s| slab.rank_of(s)).collect::<Vec<_>>();
+        assert_eq!(ranks, [Some(4), Some(0), Some(3), Some(2), Some(1), None]);
+    }
+
+    #[test]
+    fn nth() {
+        let mut slab = (0..4).collect::<LruSlab<_>>();
+        slab.get_mut(0);
+        assert_eq!(slab.nth_mru(1), Some(3));
+        assert_eq!(slab.nth_lru(0), Some(1));
+        assert_eq!(slab.nth_lru(3), Some(0));
+        assert_eq!(slab.nth_mru(4), None);
+    }
+
+    #[test]
+    fn find() {
+        let mut slab = [1, 2, 3, 4].into_iter().collect::<LruSlab<_>>();
+        slab.get_mut(1);
+        assert_eq!(slab.find(|&x| x % 2 == 0), Some((1, &2)));
+        assert_eq!(slab.find_map(|&x| (x > 2).then_some(x * 10)), Some((3, 40)));
+        assert_eq!(slab.find(|&x| x > 4), None);
+    }
+
+    #[test]
+    fn neighbors() {
+        let mut slab = (0..3).collect::<LruSlab<_>>();
+        slab.get_mut(0);
+        assert_eq!(slab.next_older(0), Some(2));
+        assert_eq!(slab.next_newer(2), Some(0));
+        assert_eq!(slab.next_newer(0), None);
+        assert_eq!(slab.next_older(1), None);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut slab = (0..10).collect::<LruSlab<_>>();
+        slab.reverse();
+        assert!(slab.iter().map(|(s, _)| s).eq(0..10));
+        assert_eq!(slab.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn rotate() {
+        let mut slab = (0..3).collect::<LruSlab<_>>();
+        slab.rotate();
+        assert!(slab.iter().map(|(s, _)| s).eq([1, 0, 2]));
+    }
+
+    #[test]
+    fn from_ordered_iter() {
+        let slab = LruSlab::from_ordered_iter(['a', 'b', 'c']);
+        assert!(slab.iter().eq([(0, &'a'), (1, &'b'), (2, &'c')]));
+        slab.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn lru_slab_macro() {
+        let slab: LruSlab<u8> = lru_slab![];
+        assert!(slab.is_empty());
+        let slab = lru_slab![1, 2, 3,];
+        assert!(slab.iter().eq([(0, &1), (1, &2), (2, &3)]));
+    }
+
+    #[test]
+    fn from_collections() {
+        let slab = LruSlab::from(alloc::vec!['a', 'b']);
+        assert!(slab.iter().eq([(1, &'b'), (0, &'a')]));
+        assert_eq!(slab.capacity(), 2);
+        assert!(LruSlab::from(['a', 'b']).iter().eq(slab.iter()));
+        assert!(LruSlab::try_from_vec(alloc::vec![()]).is_ok());
+    }
+
+    #[test]
+    fn const_new() {
+        const EMPTY: LruSlab<u8> = LruSlab::new();
+        let mut slab = EMPTY;
+        assert_eq!(slab.capacity(), 0);
+        slab.insert(0);
+        assert_eq!(slab.lru(), Some(0));
+    }
+
+    #[test]
+    fn iter_shortcuts() {
+        let mut slab = (0..5).collect::<LruSlab<_>>();
+        let mut iter = slab.iter();
+        assert_eq!(iter.nth(1), Some((3, &3)));
+        assert_eq!(iter.nth_back(1), Some((1, &1)));
+        assert_eq!(iter.clone().count(), 1);
+        assert_eq!(iter.clone().last(), Some((2, &2)));
+        assert_eq!(iter.nth(1), None);
+        assert_eq!(iter.next_back(), None);
+        let mut iter = slab.iter_mut();
+        assert_eq!(iter.nth(5), None);
+        assert_eq!(iter.next(), None);
+        assert_eq!(slab.iter_mut().last(), Some((0, &mut 0)));
+    }
+
+    #[test]
+    fn unchecked() {
+        let mut slab = LruSlab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        unsafe {
+            assert_eq!(*slab.peek_unchecked(a), 'a');
+            *slab.get_unchecked_mut(a) = 'c';
+            *slab.peek_unchecked_mut(b) = 'd';
+        }
+        assert_eq!(slab.pop_lru(), Some((b, 'd')));
+        assert_eq!(slab.pop_lru(), Some((a, 'c')));
+    }
+
+    #[test]
+    fn fallible() {
+        use allocator_api2::alloc::AllocError;
+        use core::{alloc::Layout, ptr::NonNull};
+
+        #[derive(Clone)]
+        struct Failing;
+
+        unsafe impl Allocator for Failing {
+            fn allocate(&self, _: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                Err(AllocError)
+            }
+
+            unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {}
+        }
+
+        let mut slab = LruSlab::new_in(Failing);
+        let e = slab.try_insert('a').unwrap_err();
+        assert_eq!(e.value, 'a');
+        assert_eq!(e.reason, ReserveError::AllocFailed);
+        assert_eq!(slab.try_reserve(1), Err(ReserveError::AllocFailed));
+        slab.check_invariants().unwrap();
+
+        fn cycle(slab: &mut LruSlab<char>) -> Result<char, Box<dyn core::error::Error>> {
+            let slot = slab.try_insert('b')?;
+            slab.try_remove(slot)?;
+            Ok(slab.try_remove(slot)?)
+        }
+        let mut slab = LruSlab::new();
+        let e = cycle(&mut slab).unwrap_err();
+        assert_eq!(format!("{e}"), "slot 0 is vacant");
+        assert_eq!(
+            slab.try_reserve(u32::MAX),
+            Err(ReserveError::CapacityOverflow)
+        );
+        slab.try_reserve(10).unwrap();
+        assert!(slab.capacity() >= 10);
+    }
+
+    #[test]
+    fn custom_allocator() {
+        use allocator_api2::alloc::AllocError;
+        use core::{alloc::Layout, cell::Cell, ptr::NonNull};
+
+        #[derive(Clone, Copy)]
+        struct Counting<'a>(&'a Cell<usize>);
+
+        unsafe impl Allocator for Counting<'_> {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.0.set(self.0.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let count = Cell::new(0);
+        let mut slab = LruSlab::new_in(Counting(&count));
+        slab.insert('a');
+        slab.record_access(0);
+        // Values, links, metadata, occupancy, and recorded accesses
+        assert_eq!(count.get(), 5);
+        let copy = slab.clone();
+        assert_eq!(copy.peek(0), &'a');
+        assert!(count.get() > 5);
+    }
 }
@@ -0,0 +1,355 @@
+//! Low Inter-reference Recency Set replacement
+
+use alloc::boxed::Box;
+
+use crate::{
+    list::{Link, Links, List},
+    tag_index::TagIndex,
+};
+
+/// A fixed-capacity table that evicts according to the LIRS policy
+///
+/// Entries are ranked by inter-reference recency: the number of distinct entries accessed between
+/// their two most recent uses. Most of the capacity is reserved for entries with low
+/// inter-reference recency (LIR). The remainder holds high inter-reference recency (HIR) entries,
+/// which are always evicted first, so entries that are touched only once never displace the LIR
+/// set.
+///
+/// Recency is tracked by a stack that also retains ghosts of recently evicted HIR entries.
+/// Inserting a tag whose ghost is still in the stack proves its reuse distance is short, and it
+/// returns as LIR. Tags are supplied by the caller on insert and should identify the value, e.g. by
+/// hashing its key. Ghosts occupy slots without storing values, so at most `2 * capacity` slots are
+/// in use.
+pub struct LirsSlab<T> {
+    slots: Box<[Slot<T>]>,
+    /// Recency stack of LIR entries and the HIR entries and ghosts more recent than the oldest LIR
+    /// entry, threaded through `Slot::stack`
+    stack: List,
+    /// Resident HIR entries, the next to be evicted at the front, threaded through `Slot::queue`
+    queue: List,
+    /// Ghosts, most recently evicted first, threaded through `Slot::queue`
+    ghost_list: List,
+    /// Threaded through `Slot::queue`
+    free: List,
+    /// Locates ghosts by tag
+    ghosts: TagIndex,
+    capacity: u32,
+    /// Maximum number of LIR entries
+    lir_capacity: u32,
+    lirs: u32,
+}
+
+impl<T> LirsSlab<T> {
+    /// Create a [`LirsSlab`] that holds at most `capacity` values
+    ///
+    /// One percent of the capacity, and at least one slot, is reserved for HIR entries.
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        let len = capacity.checked_mul(2).expect("capacity too large");
+        assert!(len != u32::MAX, "capacity too large");
+        let mut slots = (0..len)
+            .map(|_| Slot {
+                value: None,
+                tag: 0,
+                state: State::Free,
+                in_stack: false,
+                stack: Link::UNLINKED,
+                queue: Link::UNLINKED,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..len {
+            free.push_back(&mut slots[..], slot);
+        }
+        Self {
+            slots,
+            stack: List::EMPTY,
+            queue: List::EMPTY,
+            ghost_list: List::EMPTY,
+            free,
+            ghosts: TagIndex::new(capacity),
+            capacity,
+            lir_capacity: capacity - (capacity / 100).max(1),
+            lirs: 0,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.lirs + self.queue.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Insert a value identified by `tag`, returning the slot it was stored in and the slot and
+    /// value of any entry evicted to make room
+    pub fn insert(&mut self, tag: u64, value: T) -> (u32, Option<(u32, T)>) {
+        // Detach any ghost first so that making room can't discard it
+        let ghost = self.ghosts.remove(tag);
+        if let Some(ghost) = ghost {
+            self.ghost_list.remove(&mut self.slots[..], ghost);
+        }
+
+        let evicted = match self.len() == self.capacity {
+            true => Some(self.evict()),
+            false => None,
+        };
+
+        if let Some(slot) = ghost {
+            // Reused while still on the stack, so its inter-reference recency is low
+            let entry = &mut self.slots[slot as usize];
+            entry.value = Some(value);
+            entry.state = State::Lir;
+            self.lirs += 1;
+            self.stack.remove(&mut Stack(&mut self.slots[..]), slot);
+            self.stack.push_front(&mut Stack(&mut self.slots[..]), slot);
+            self.rebalance();
+            return (slot, evicted);
+        }
+
+        let slot = self
+            .free
+            .pop_front(&mut self.slots[..])
+            .expect("no free slots");
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        entry.value = Some(value);
+        entry.tag = tag;
+        entry.in_stack = true;
+        if self.lirs < self.lir_capacity {
+            // Until the LIR set fills up, nothing has a known inter-reference recency
+            entry.state = State::Lir;
+            self.lirs += 1;
+        } else {
+            entry.state = State::Hir;
+            self.queue.push_back(&mut self.slots[..], slot);
+        }
+        self.stack.push_front(&mut Stack(&mut self.slots[..]), slot);
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    ///
+    /// No ghost is left behind.
+    pub fn remove(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        let value = entry.value.take().expect("removing empty slot");
+        match entry.state {
+            State::Lir => self.lirs -= 1,
+            State::Hir => self.queue.remove(&mut self.slots[..], slot),
+            _ => unreachable!(),
+        }
+        if self.slots[slot as usize].in_stack {
+            self.stack.remove(&mut Stack(&mut self.slots[..]), slot);
+        }
+        self.release(slot);
+        self.prune();
+        value
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &self.slots[slot as usize];
+        assert!(entry.value.is_some(), "accessing empty slot");
+        match (entry.state, entry.in_stack) {
+            (State::Lir, _) => {
+                self.stack.remove(&mut Stack(&mut self.slots[..]), slot);
+                self.stack.push_front(&mut Stack(&mut self.slots[..]), slot);
+                self.prune();
+            }
+            (State::Hir, true) => {
+                // Reused while still on the stack, so its inter-reference recency is lower than
+                // that of the oldest LIR entry
+                self.slots[slot as usize].state = State::Lir;
+                self.lirs += 1;
+                self.queue.remove(&mut self.slots[..], slot);
+                self.stack.remove(&mut Stack(&mut self.slots[..]), slot);
+                self.stack.push_front(&mut Stack(&mut self.slots[..]), slot);
+                self.rebalance();
+            }
+            (State::Hir, false) => {
+                self.slots[slot as usize].in_stack = true;
+                self.stack.push_front(&mut Stack(&mut self.slots[..]), slot);
+                self.queue.remove(&mut self.slots[..], slot);
+                self.queue.push_back(&mut self.slots[..], slot);
+            }
+            _ => unreachable!(),
+        }
+        self.peek_mut(slot)
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Evict the oldest resident HIR entry, leaving a ghost if it's still on the stack
+    fn evict(&mut self) -> (u32, T) {
+        let slot = self
+            .queue
+            .pop_front(&mut self.slots[..])
+            .expect("no HIR entries to evict");
+        let entry = &mut self.slots[slot as usize];
+        let value = entry.value.take().unwrap();
+        if !entry.in_stack {
+            self.release(slot);
+            return (slot, value);
+        }
+        entry.state = State::Ghost;
+        if let Some(stale) = self.ghosts.insert(entry.tag, slot) {
+            // The same tag was evicted before; only the newest ghost is meaningful
+            self.forget(stale);
+        }
+        self.ghost_list.push_front(&mut self.slots[..], slot);
+        if self.ghost_list.len > self.capacity {
+            let oldest = self.ghost_list.back().unwrap();
+            self.ghosts.remove(self.slots[oldest as usize].tag);
+            self.forget(oldest);
+        }
+        (slot, value)
+    }
+
+    /// Demote the oldest LIR entry if the LIR set has outgrown its capacity
+    fn rebalance(&mut self) {
+        self.prune();
+        if self.lirs > self.lir_capacity {
+            let slot = self.stack.back().unwrap();
+            debug_assert!(self.slots[slot as usize].state == State::Lir);
+            self.slots[slot as usize].state = State::Hir;
+            self.lirs -= 1;
+            self.queue.push_back(&mut self.slots[..], slot);
+        }
+        self.prune();
+    }
+
+    /// Pop HIR entries and ghosts off the bottom of the stack until it ends with an LIR entry, so
+    /// that everything on the stack is more recent than the oldest LIR entry
+    fn prune(&mut self) {
+        while let Some(slot) = self.stack.back() {
+            match self.slots[slot as usize].state {
+                State::Lir => break,
+                State::Hir => {
+                    self.stack.remove(&mut Stack(&mut self.slots[..]), slot);
+                    self.slots[slot as usize].in_stack = false;
+                }
+                State::Ghost => {
+                    self.ghosts.remove(self.slots[slot as usize].tag);
+                    self.forget(slot);
+                }
+                State::Free => unreachable!("corrupt LIRS stack"),
+            }
+        }
+    }
+
+    /// Free a ghost slot that has already been removed from the index
+    fn forget(&mut self, slot: u32) {
+        self.ghost_list.remove(&mut self.slots[..], slot);
+        self.stack.remove(&mut Stack(&mut self.slots[..]), slot);
+        self.release(slot);
+    }
+
+    /// Free a slot that's no longer in any list but the stack
+    fn release(&mut self, slot: u32) {
+        let entry = &mut self.slots[slot as usize];
+        entry.state = State::Free;
+        entry.in_stack = false;
+        self.free.push_front(&mut self.slots[..], slot);
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    tag: u64,
+    state: State,
+    in_stack: bool,
+    stack: Link,
+    /// Link in the HIR queue, the ghost list, or the free list
+    queue: Link,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum State {
+    Free,
+    /// Resident with low inter-reference recency
+    Lir,
+    /// Resident with high inter-reference recency
+    Hir,
+    /// Non-resident HIR entry remembered by the stack
+    Ghost,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].queue
+    }
+}
+
+/// Accesses the stack links of slots
+struct Stack<'a, T>(&'a mut [Slot<T>]);
+
+impl<T> Links for Stack<'_, T> {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self.0[slot as usize].stack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resists_loop() {
+        // Cycling over one more entry than fits evicts every entry under LRU
+        let mut cache = LirsSlab::new(8);
+        let mut slots = [None; 9];
+        let mut hits = 0;
+        for _ in 0..10 {
+            for tag in 0..9 {
+                match slots[tag] {
+                    Some(slot) => {
+                        hits += 1;
+                        cache.get_mut(slot);
+                    }
+                    None => {
+                        let (slot, evicted) = cache.insert(tag as u64, tag);
+                        if let Some((_, old)) = evicted {
+                            slots[old] = None;
+                        }
+                        slots[tag] = Some(slot);
+                    }
+                }
+            }
+        }
+        assert!(hits > 50, "{hits} hits");
+    }
+
+    #[test]
+    fn hir_evicted_first() {
+        let mut cache = LirsSlab::new(3);
+        let (a, _) = cache.insert(0, 'a');
+        cache.insert(1, 'b');
+        // The LIR set is full, so later entries are HIR
+        cache.insert(2, 'c');
+        let (_, evicted) = cache.insert(3, 'd');
+        assert_eq!(evicted.map(|(_, x)| x), Some('c'));
+        assert_eq!(*cache.peek(a), 'a');
+        // 'c' is remembered as a ghost, and comes back as LIR, demoting the oldest LIR entry
+        let (_, evicted) = cache.insert(2, 'c');
+        assert_eq!(evicted.map(|(_, x)| x), Some('d'));
+        let (_, evicted) = cache.insert(4, 'e');
+        assert_eq!(evicted.map(|(_, x)| x), Some('a'));
+    }
+}
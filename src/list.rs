@@ -0,0 +1,174 @@
+//! Intrusive doubly-linked lists threaded through slot indices
+
+use crate::NONE;
+
+/// Neighbors of a slot within whichever list it currently belongs to
+///
+/// Aligned like a `u64` so that a record never straddles cache lines and is loaded or stored whole.
+#[derive(Debug, Copy, Clone)]
+#[repr(C, align(8))]
+pub(crate) struct Link {
+    pub(crate) prev: u32,
+    pub(crate) next: u32,
+}
+
+impl Link {
+    pub(crate) const UNLINKED: Self = Self {
+        prev: NONE,
+        next: NONE,
+    };
+}
+
+/// Storage that can look up the [`Link`] of a slot
+pub(crate) trait Links {
+    fn link(&mut self, slot: u32) -> &mut Link;
+}
+
+impl Links for [Link] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize]
+    }
+}
+
+/// Endpoints and length of a list whose links are stored elsewhere
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct List {
+    /// Front of the list; most recently used, for recency lists
+    pub(crate) head: u32,
+    /// Back of the list; least recently used, for recency lists
+    pub(crate) tail: u32,
+    pub(crate) len: u32,
+}
+
+// Some operations are only needed by heap-backed tables
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+impl List {
+    pub(crate) const EMPTY: Self = Self {
+        head: NONE,
+        tail: NONE,
+        len: 0,
+    };
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn front(&self) -> Option<u32> {
+        (self.head != NONE).then_some(self.head)
+    }
+
+    pub(crate) fn back(&self) -> Option<u32> {
+        (self.tail != NONE).then_some(self.tail)
+    }
+
+    /// Add `slot` to the front of the list
+    pub(crate) fn push_front(&mut self, links: &mut (impl Links + ?Sized), slot: u32) {
+        *links.link(slot) = Link {
+            prev: NONE,
+            next: self.head,
+        };
+        if self.head == NONE {
+            self.tail = slot;
+        } else {
+            links.link(self.head).prev = slot;
+        }
+        self.head = slot;
+        self.len += 1;
+    }
+
+    /// Add `slot` to the back of the list
+    pub(crate) fn push_back(&mut self, links: &mut (impl Links + ?Sized), slot: u32) {
+        *links.link(slot) = Link {
+            prev: self.tail,
+            next: NONE,
+        };
+        if self.tail == NONE {
+            self.head = slot;
+        } else {
+            links.link(self.tail).next = slot;
+        }
+        self.tail = slot;
+        self.len += 1;
+    }
+
+    /// Add `slot` immediately before `before`, which must be in the list
+    pub(crate) fn insert_before(
+        &mut self,
+        links: &mut (impl Links + ?Sized),
+        before: u32,
+        slot: u32,
+    ) {
+        let prev = links.link(before).prev;
+        if prev == NONE {
+            self.push_front(links, slot);
+            return;
+        }
+        *links.link(slot) = Link { prev, next: before };
+        links.link(prev).next = slot;
+        links.link(before).prev = slot;
+        self.len += 1;
+    }
+
+    /// Add `slot` immediately after `after`, which must be in the list
+    pub(crate) fn insert_after(
+        &mut self,
+        links: &mut (impl Links + ?Sized),
+        after: u32,
+        slot: u32,
+    ) {
+        let next = links.link(after).next;
+        if next == NONE {
+            self.push_back(links, slot);
+            return;
+        }
+        *links.link(slot) = Link { prev: after, next };
+        links.link(next).prev = slot;
+        links.link(after).next = slot;
+        self.len += 1;
+    }
+
+    /// Remove `slot`, which must be in the list
+    pub(crate) fn remove(&mut self, links: &mut (impl Links + ?Sized), slot: u32) {
+        let Link { prev, next } = *links.link(slot);
+        if prev == NONE {
+            self.head = next;
+        } else {
+            links.link(prev).next = next;
+        }
+        if next == NONE {
+            self.tail = prev;
+        } else {
+            links.link(next).prev = prev;
+        }
+        *links.link(slot) = Link::UNLINKED;
+        self.len -= 1;
+    }
+
+    pub(crate) fn pop_front(&mut self, links: &mut (impl Links + ?Sized)) -> Option<u32> {
+        let slot = self.front()?;
+        self.remove(links, slot);
+        Some(slot)
+    }
+
+    pub(crate) fn pop_back(&mut self, links: &mut (impl Links + ?Sized)) -> Option<u32> {
+        let slot = self.back()?;
+        self.remove(links, slot);
+        Some(slot)
+    }
+
+    /// The slot after `slot`, wrapping around to the front so the list can be treated as a ring
+    pub(crate) fn next_cyclic(&self, links: &mut (impl Links + ?Sized), slot: u32) -> u32 {
+        match links.link(slot).next {
+            NONE => self.head,
+            next => next,
+        }
+    }
+
+    /// The slot before `slot`, wrapping around to the back so the list can be treated as a ring
+    pub(crate) fn prev_cyclic(&self, links: &mut (impl Links + ?Sized), slot: u32) -> u32 {
+        match links.link(slot).prev {
+            NONE => self.tail,
+            prev => prev,
+        }
+    }
+}
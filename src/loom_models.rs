@@ -0,0 +1,80 @@
+//! Exhaustive checks of the concurrent types' internals
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --features std loom_models`.
+
+use loom::{sync::Arc, thread};
+
+use crate::{AtomicLruSlab, ShardedLruSlab, SyncLruSlab};
+
+fn model(f: impl Fn() + Sync + Send + 'static) {
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound.get_or_insert(3);
+    builder.check(f);
+}
+
+#[test]
+fn sync_reads_race_removal() {
+    model(|| {
+        let slab = Arc::new(SyncLruSlab::new());
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        let reader = {
+            let slab = slab.clone();
+            thread::spawn(move || {
+                // Fill the stripe so that it's drained concurrently with the removal
+                for _ in 0..2 {
+                    if let Some(x) = slab.read(b) {
+                        assert_eq!(*x, 'b');
+                    }
+                }
+                assert_eq!(slab.read(a).map(|x| *x), Some('a'));
+            })
+        };
+        assert_eq!(slab.remove(b), Some('b'));
+        reader.join().unwrap();
+        let mut slab = Arc::try_unwrap(slab).ok().unwrap().into_inner();
+        assert_eq!(slab.len(), 1);
+        assert_eq!(slab.pop_lru(), Some((a, 'a')));
+    });
+}
+
+#[test]
+fn sharded_pop_races_get() {
+    model(|| {
+        let slab = Arc::new(ShardedLruSlab::new(2));
+        let a = slab.insert(0, 'a');
+        let b = slab.insert(1, 'b');
+        let popper = {
+            let slab = slab.clone();
+            thread::spawn(move || slab.pop_lru(0, 2))
+        };
+        slab.get_mut(a, |x| assert_eq!(*x, 'a'));
+        let popped = popper.join().unwrap().unwrap();
+        // Whichever was evicted, the other remains, and nothing is lost or duplicated
+        let (rest, value) = if popped.0 == a { (b, 'b') } else { (a, 'a') };
+        assert_eq!(slab.len(), 1);
+        assert_eq!(slab.remove(rest), Some(value));
+    });
+}
+
+#[test]
+fn atomic_reads_race_each_other() {
+    model(|| {
+        let mut slab = AtomicLruSlab::with_samples(2, 8);
+        let a = slab.insert('a').0;
+        let b = slab.insert('b').0;
+        let slab = Arc::new(slab);
+        let reader = {
+            let slab = slab.clone();
+            thread::spawn(move || assert_eq!(*slab.get(a), 'a'))
+        };
+        assert_eq!(*slab.get(b), 'b');
+        reader.join().unwrap();
+        let mut slab = Arc::try_unwrap(slab).ok().unwrap();
+        let (_, evicted) = slab.insert('c');
+        assert!(
+            matches!(evicted, Some((x, 'a')) if x == a)
+                || matches!(evicted, Some((x, 'b')) if x == b)
+        );
+    });
+}
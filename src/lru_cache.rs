@@ -0,0 +1,213 @@
+//! Drop-in replacement for the `lru` crate's cache
+
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    num::NonZeroUsize,
+};
+
+use hashbrown::DefaultHashBuilder;
+
+use crate::LruMap;
+
+/// A bounded cache with the same interface as `lru::LruCache`, backed by an [`LruMap`]
+///
+/// Inserting into a full cache evicts the least recently used entry.
+#[derive(Clone)]
+pub struct LruCache<K, V, S = DefaultHashBuilder> {
+    map: LruMap<K, V, S>,
+    cap: NonZeroUsize,
+}
+
+impl<K: Hash + Eq, V> LruCache<K, V> {
+    /// Create an empty [`LruCache`] that holds at most `cap` entries
+    pub fn new(cap: NonZeroUsize) -> Self {
+        Self::with_hasher(cap, DefaultHashBuilder::default())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> LruCache<K, V, S> {
+    /// Create an empty [`LruCache`] that holds at most `cap` entries and hashes keys with
+    /// `hash_builder`
+    pub fn with_hasher(cap: NonZeroUsize, hash_builder: S) -> Self {
+        Self {
+            map: LruMap::with_capacity_and_hasher(slab_capacity(cap), hash_builder),
+            cap,
+        }
+    }
+
+    /// Insert a value, returning the value previously associated with `k`, if any
+    ///
+    /// If the cache is full and `k` is new, the least recently used entry is evicted.
+    pub fn put(&mut self, k: K, v: V) -> Option<V> {
+        match self.replace(&k, v) {
+            Ok(old) => Some(old),
+            Err(v) => {
+                if self.len() == self.cap.get() {
+                    self.map.pop_lru();
+                }
+                self.map.insert(k, v);
+                None
+            }
+        }
+    }
+
+    /// Insert a value, returning the displaced entry: either the previous value for `k`, or the
+    /// least recently used entry if it had to be evicted
+    pub fn push(&mut self, k: K, v: V) -> Option<(K, V)> {
+        match self.replace(&k, v) {
+            Ok(old) => Some((k, old)),
+            Err(v) => {
+                let evicted = match self.len() == self.cap.get() {
+                    true => self.map.pop_lru(),
+                    false => None,
+                };
+                self.map.insert(k, v);
+                evicted
+            }
+        }
+    }
+
+    /// Mark the entry for `k` as the most recently used and access its value
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(k)
+    }
+
+    /// Mark the entry for `k` as the most recently used and access its value uniquely
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get_mut(k)
+    }
+
+    /// Access the value for `k` without marking it as most recently used
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.peek(k)
+    }
+
+    /// Access the value for `k` uniquely without marking it as most recently used
+    pub fn peek_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.peek_mut(k)
+    }
+
+    /// The least recently used entry, if any
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.map.lru()
+    }
+
+    /// Whether an entry for `k` is stored
+    pub fn contains<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(k)
+    }
+
+    /// Remove the entry for `k`, returning its value
+    pub fn pop<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(k)
+    }
+
+    /// Remove the least recently used entry, returning it
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        self.map.pop_lru()
+    }
+
+    /// Number of entries stored
+    pub fn len(&self) -> usize {
+        self.map.len() as usize
+    }
+
+    /// Whether no entries are stored
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Maximum number of entries stored
+    pub fn cap(&self) -> NonZeroUsize {
+        self.cap
+    }
+
+    /// Change the maximum number of entries, evicting the least recently used entries if necessary
+    pub fn resize(&mut self, cap: NonZeroUsize) {
+        while self.len() > cap.get() {
+            self.map.pop_lru();
+        }
+        self.cap = cap;
+    }
+
+    /// Remove all entries
+    pub fn clear(&mut self) {
+        while self.map.pop_lru().is_some() {}
+    }
+
+    /// Replace the value for `k` if present, marking it as most recently used, or hand `v` back
+    fn replace(&mut self, k: &K, v: V) -> Result<V, V> {
+        match self.map.get_mut(k) {
+            Some(old) => Ok(core::mem::replace(old, v)),
+            None => Err(v),
+        }
+    }
+
+    /// Walk the cache from most to least recently used
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator + '_ {
+        self.map.iter()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for LruCache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruCache")
+            .field("len", &self.map.len())
+            .field("cap", &self.cap)
+            .finish()
+    }
+}
+
+/// Convert a cache capacity to a slab capacity, panicking if it's too large
+fn slab_capacity(cap: NonZeroUsize) -> u32 {
+    u32::try_from(cap.get())
+        .ok()
+        .filter(|&x| x != u32::MAX)
+        .expect("capacity too large")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_evicts_lru() {
+        let mut cache = LruCache::new(NonZeroUsize::new(2).unwrap());
+        assert_eq!(cache.put("a", 1), None);
+        assert_eq!(cache.put("b", 2), None);
+        assert_eq!(cache.get("a"), Some(&1));
+        assert_eq!(cache.put("c", 3), None);
+        assert!(!cache.contains("b"));
+        assert_eq!(cache.put("a", 4), Some(1));
+        assert_eq!(cache.push("d", 5), Some(("c", 3)));
+        cache.resize(NonZeroUsize::new(1).unwrap());
+        assert_eq!(cache.pop_lru(), Some(("d", 5)));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+//! Keyed access to an [`LruSlab`]
+
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    mem,
+};
+
+use hashbrown::{DefaultHashBuilder, HashTable};
+
+use crate::LruSlab;
+
+/// A map that tracks the least recently used key in constant time
+///
+/// Entries are stored in an [`LruSlab`], and a hash table maps each key to its slot, so keys are
+/// stored only once.
+#[derive(Clone)]
+pub struct LruMap<K, V, S = DefaultHashBuilder> {
+    slab: LruSlab<(K, V)>,
+    table: HashTable<u32>,
+    hasher: S,
+}
+
+impl<K, V> LruMap<K, V> {
+    /// Create an empty [`LruMap`]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create an [`LruMap`] that can store at least `capacity` entries without reallocating
+    pub fn with_capacity(capacity: u32) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> LruMap<K, V, S> {
+    /// Create an empty [`LruMap`] that hashes keys with `hasher`
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    /// Create an [`LruMap`] that can store at least `capacity` entries without reallocating, and
+    /// hashes keys with `hasher`
+    pub fn with_capacity_and_hasher(capacity: u32, hasher: S) -> Self {
+        Self {
+            slab: LruSlab::with_capacity(capacity),
+            table: HashTable::with_capacity(capacity as usize),
+            hasher,
+        }
+    }
+
+    /// Whether no entries are stored
+    pub fn is_empty(&self) -> bool {
+        self.slab.is_empty()
+    }
+
+    /// Number of entries stored
+    pub fn len(&self) -> u32 {
+        self.slab.len()
+    }
+
+    /// The least recently used entry, if any
+    pub fn lru(&self) -> Option<(&K, &V)> {
+        let (key, value) = self.slab.peek(self.slab.lru()?);
+        Some((key, value))
+    }
+
+    /// Walk the map from most to least recently used
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator + '_ {
+        self.slab.iter().map(|(_, (key, value))| (key, value))
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> LruMap<K, V, S> {
+    /// Insert a value, marking it as the most recently used and returning the value previously
+    /// associated with `key`, if any
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hasher.hash_one(&key);
+        let slab = &mut self.slab;
+        if let Some(&slot) = self.table.find(hash, |&x| slab.peek(x).0 == key) {
+            return Some(mem::replace(&mut slab.get_mut(slot).1, value));
+        }
+        let slot = slab.insert((key, value));
+        let hasher = &self.hasher;
+        self.table
+            .insert_unique(hash, slot, |&x| hasher.hash_one(&slab.peek(x).0));
+        None
+    }
+
+    /// Mark the entry for `key` as the most recently used and access its value
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|x| &*x)
+    }
+
+    /// Mark the entry for `key` as the most recently used and access its value uniquely
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(key)?;
+        Some(&mut self.slab.get_mut(slot).1)
+    }
+
+    /// Access the value for `key` without marking it as most recently used
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(key)?;
+        Some(&self.slab.peek(slot).1)
+    }
+
+    /// Access the value for `key` uniquely without marking it as most recently used
+    pub fn peek_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(key)?;
+        Some(&mut self.slab.peek_mut(slot).1)
+    }
+
+    /// Whether an entry for `key` is stored
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Remove the entry for `key`, returning its value
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher.hash_one(key);
+        let slab = &self.slab;
+        let (slot, _) = self
+            .table
+            .find_entry(hash, |&x| slab.peek(x).0.borrow() == key)
+            .ok()?
+            .remove();
+        Some(self.slab.remove(slot).1)
+    }
+
+    /// Remove the least recently used entry, returning it
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (slot, (key, value)) = self.slab.pop_lru()?;
+        let hash = self.hasher.hash_one(&key);
+        self.table
+            .find_entry(hash, |&x| x == slot)
+            .expect("corrupt index")
+            .remove();
+        Some((key, value))
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<u32>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher.hash_one(key);
+        self.table
+            .find(hash, |&x| self.slab.peek(x).0.borrow() == key)
+            .copied()
+    }
+}
+
+impl<K, V, S: Default> Default for LruMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for LruMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_order() {
+        let mut map = LruMap::new();
+        assert_eq!(map.insert("a", 1), None);
+        map.insert("b", 2);
+        map.insert("c", 3);
+        assert_eq!(map.insert("a", 4), Some(1));
+        assert_eq!(map.get("b"), Some(&2));
+        assert_eq!(map.peek("c"), Some(&3));
+        assert_eq!(map.pop_lru(), Some(("c", 3)));
+        assert_eq!(map.remove("b"), Some(2));
+        assert!(!map.contains_key("b"));
+        assert_eq!(map.lru(), Some((&"a", &4)));
+        assert_eq!(map.len(), 1);
+    }
+}
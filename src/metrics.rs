@@ -0,0 +1,127 @@
+//! Publishing [`Stats`] through the `metrics` facade
+
+use alloc::format;
+
+use ::metrics::{counter, gauge, Counter, Gauge};
+use allocator_api2::alloc::Allocator;
+
+use crate::{LruSlab, Stats};
+
+/// Handles for publishing the [`Stats`], occupancy, and capacity of an [`LruSlab`]
+///
+/// Each metric is named by appending a suffix to a prefix chosen at construction: `.hits`,
+/// `.inserts`, `.removals`, `.evictions`, and `.grows` are counters, while `.hit_ratio`, `.len`,
+/// and `.capacity` are gauges. The hit ratio is hits divided by hits plus inserts, treating every
+/// insert as a miss.
+pub struct SlabMetrics {
+    hits: Counter,
+    inserts: Counter,
+    removals: Counter,
+    evictions: Counter,
+    grows: Counter,
+    hit_ratio: Gauge,
+    len: Gauge,
+    capacity: Gauge,
+}
+
+impl SlabMetrics {
+    /// Register metrics whose names begin with `prefix` with the installed recorder
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            hits: counter!(format!("{prefix}.hits")),
+            inserts: counter!(format!("{prefix}.inserts")),
+            removals: counter!(format!("{prefix}.removals")),
+            evictions: counter!(format!("{prefix}.evictions")),
+            grows: counter!(format!("{prefix}.grows")),
+            hit_ratio: gauge!(format!("{prefix}.hit_ratio")),
+            len: gauge!(format!("{prefix}.len")),
+            capacity: gauge!(format!("{prefix}.capacity")),
+        }
+    }
+
+    /// Publish the current state of `slab`
+    pub fn record<T, A: Allocator + Clone>(&self, slab: &LruSlab<T, A>) {
+        let Stats {
+            hits,
+            inserts,
+            removals,
+            evictions,
+            grows,
+        } = slab.stats();
+        self.hits.absolute(hits);
+        self.inserts.absolute(inserts);
+        self.removals.absolute(removals);
+        self.evictions.absolute(evictions);
+        self.grows.absolute(grows);
+        let lookups = hits + inserts;
+        if lookups != 0 {
+            self.hit_ratio.set(hits as f64 / lookups as f64);
+        }
+        self.len.set(slab.len());
+        self.capacity.set(slab.capacity());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, sync::Arc, vec::Vec};
+    use core::{
+        cell::RefCell,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use ::metrics::{Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+    use super::*;
+
+    /// Records the latest value of every metric, as raw bits
+    #[derive(Default)]
+    struct TestRecorder(RefCell<Vec<(String, Arc<AtomicU64>)>>);
+
+    impl TestRecorder {
+        fn register(&self, key: &Key) -> Arc<AtomicU64> {
+            let value = Arc::new(AtomicU64::new(0));
+            let name = String::from(key.name());
+            self.0.borrow_mut().push((name, value.clone()));
+            value
+        }
+
+        fn get(&self, name: &str) -> u64 {
+            let metrics = self.0.borrow();
+            let (_, value) = metrics.iter().find(|(n, _)| n == name).unwrap();
+            value.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.register(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.register(key))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn publishes_stats() {
+        let recorder = TestRecorder::default();
+        let metrics = ::metrics::with_local_recorder(&recorder, || SlabMetrics::new("cache"));
+        let mut slab = LruSlab::new();
+        let a = slab.insert(());
+        slab.get_mut(a);
+        metrics.record(&slab);
+        assert_eq!(recorder.get("cache.hits"), 1);
+        assert_eq!(recorder.get("cache.inserts"), 1);
+        assert_eq!(f64::from_bits(recorder.get("cache.hit_ratio")), 0.5);
+        assert_eq!(f64::from_bits(recorder.get("cache.len")), 1.0);
+    }
+}
@@ -0,0 +1,285 @@
+//! The Multi-Queue replacement algorithm
+
+use alloc::boxed::Box;
+
+use crate::{
+    list::{Link, Links, List},
+    tag_index::TagIndex,
+};
+
+/// A fixed-capacity table that evicts according to the Multi-Queue policy
+///
+/// Entries are kept in several LRU queues, where an entry used `f` times lives in queue
+/// `log2(f)`, and the least recently used entry of the lowest nonempty queue is evicted. An entry
+/// that goes unused for longer than its lifetime, measured by a logical clock that ticks on every
+/// insert and use, is demoted one queue. Evicted entries leave ghosts that remember how often they
+/// were used, so an entry that returns resumes at its previous frequency.
+///
+/// This suits caches that sit behind another cache, where the strongest locality has already been
+/// filtered out and reuse distances are long.
+///
+/// Tags are supplied by the caller on insert and should identify the value, e.g. by hashing its
+/// key. Ghosts occupy slots without storing values, so at most `2 * capacity` slots are in use.
+pub struct MqSlab<T> {
+    slots: Box<[Slot<T>]>,
+    free: List,
+    /// Resident entries by frequency class, most recently used first
+    queues: [List; QUEUES],
+    /// Ghosts of evicted entries, most recently evicted first
+    history: List,
+    /// Locates ghosts by tag
+    ghosts: TagIndex,
+    capacity: u32,
+    /// Ticks an entry may go unused before it's demoted
+    lifetime: u64,
+    clock: u64,
+}
+
+impl<T> MqSlab<T> {
+    /// Create an [`MqSlab`] that holds at most `capacity` values
+    ///
+    /// Entries are demoted after going unused for `capacity` ticks.
+    pub fn new(capacity: u32) -> Self {
+        Self::with_lifetime(capacity, capacity.into())
+    }
+
+    /// Create an [`MqSlab`] that holds at most `capacity` values and demotes entries after going
+    /// unused for `lifetime` inserts and uses
+    ///
+    /// The lifetime should be around the typical distance between uses of a popular entry.
+    pub fn with_lifetime(capacity: u32, lifetime: u64) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        let len = capacity.checked_mul(2).expect("capacity too large");
+        assert!(len != u32::MAX, "capacity too large");
+        let mut slots = (0..len)
+            .map(|_| Slot {
+                value: None,
+                tag: 0,
+                frequency: 0,
+                expires: 0,
+                queue: Queue::Free,
+                link: Link::UNLINKED,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..len {
+            free.push_back(&mut slots[..], slot);
+        }
+        Self {
+            slots,
+            free,
+            queues: [List::EMPTY; QUEUES],
+            history: List::EMPTY,
+            ghosts: TagIndex::new(capacity),
+            capacity,
+            lifetime,
+            clock: 0,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.queues.iter().map(|x| x.len).sum()
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Insert a value identified by `tag`, returning the slot it was stored in and the slot and
+    /// value of any entry evicted to make room
+    pub fn insert(&mut self, tag: u64, value: T) -> (u32, Option<(u32, T)>) {
+        self.clock += 1;
+        // Detach any ghost first so that making room can't discard it
+        let ghost = self.ghosts.remove(tag);
+        if let Some(ghost) = ghost {
+            self.history.remove(&mut self.slots[..], ghost);
+        }
+
+        let evicted = match self.len() == self.capacity {
+            true => Some(self.evict()),
+            false => None,
+        };
+
+        let (slot, frequency) = match ghost {
+            Some(slot) => (slot, self.slots[slot as usize].frequency),
+            None => {
+                let slot = self
+                    .free
+                    .pop_front(&mut self.slots[..])
+                    .expect("no free slots");
+                (slot, 0)
+            }
+        };
+        let entry = &mut self.slots[slot as usize];
+        entry.value = Some(value);
+        entry.tag = tag;
+        entry.frequency = frequency.saturating_add(1);
+        self.enqueue(slot);
+        self.adjust();
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    ///
+    /// No ghost is left behind.
+    pub fn remove(&mut self, slot: u32) -> T {
+        self.unlink(slot);
+        let value = self.slots[slot as usize].value.take().unwrap();
+        self.release(slot);
+        value
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        self.clock += 1;
+        self.unlink(slot);
+        let entry = &mut self.slots[slot as usize];
+        entry.frequency = entry.frequency.saturating_add(1);
+        self.enqueue(slot);
+        self.adjust();
+        self.peek_mut(slot)
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Link an unlinked resident slot into the queue for its frequency, restarting its lifetime
+    fn enqueue(&mut self, slot: u32) {
+        let entry = &mut self.slots[slot as usize];
+        // floor(log2(frequency)), clamped to the last queue
+        let level = (31 - entry.frequency.leading_zeros()).min(QUEUES as u32 - 1) as u8;
+        entry.queue = Queue::Resident(level);
+        entry.expires = self.clock + self.lifetime;
+        self.queues[level as usize].push_front(&mut self.slots[..], slot);
+    }
+
+    /// Demote the least recently used entry of each queue if its lifetime has elapsed
+    fn adjust(&mut self) {
+        for level in 1..QUEUES {
+            let Some(slot) = self.queues[level].back() else {
+                continue;
+            };
+            if self.slots[slot as usize].expires >= self.clock {
+                continue;
+            }
+            self.queues[level].remove(&mut self.slots[..], slot);
+            self.queues[level - 1].push_front(&mut self.slots[..], slot);
+            let entry = &mut self.slots[slot as usize];
+            entry.queue = Queue::Resident(level as u8 - 1);
+            entry.expires = self.clock + self.lifetime;
+        }
+    }
+
+    /// Turn the least recently used entry of the lowest nonempty queue into a ghost
+    fn evict(&mut self) -> (u32, T) {
+        let slot = self
+            .queues
+            .iter()
+            .find_map(|x| x.back())
+            .expect("evicting from empty table");
+        self.unlink(slot);
+        let entry = &mut self.slots[slot as usize];
+        entry.queue = Queue::Ghost;
+        let value = entry.value.take().unwrap();
+        if let Some(stale) = self.ghosts.insert(entry.tag, slot) {
+            // The same tag was evicted before; only the newest ghost is meaningful
+            self.history.remove(&mut self.slots[..], stale);
+            self.release(stale);
+        }
+        self.history.push_front(&mut self.slots[..], slot);
+        if self.history.len > self.capacity {
+            let oldest = self.history.pop_back(&mut self.slots[..]).unwrap();
+            self.ghosts.remove(self.slots[oldest as usize].tag);
+            self.release(oldest);
+        }
+        (slot, value)
+    }
+
+    /// Remove a resident slot from its queue
+    fn unlink(&mut self, slot: u32) {
+        match self.slots[slot as usize].queue {
+            Queue::Resident(level) => self.queues[level as usize].remove(&mut self.slots[..], slot),
+            _ => panic!("accessing empty slot"),
+        }
+    }
+
+    /// Return an unlinked slot to the free list
+    fn release(&mut self, slot: u32) {
+        self.slots[slot as usize].queue = Queue::Free;
+        self.free.push_front(&mut self.slots[..], slot);
+    }
+}
+
+/// Number of frequency classes
+const QUEUES: usize = 8;
+
+struct Slot<T> {
+    value: Option<T>,
+    tag: u64,
+    /// Uses since the entry was first inserted, retained by ghosts
+    frequency: u32,
+    /// Logical time after which the entry is demoted if unused
+    expires: u64,
+    queue: Queue,
+    link: Link,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Queue {
+    Free,
+    Resident(u8),
+    Ghost,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequent_survives_scan() {
+        let mut cache = MqSlab::with_lifetime(4, 100);
+        let (hot, _) = cache.insert(0, 0);
+        for _ in 0..3 {
+            cache.get_mut(hot);
+        }
+        for tag in 1..50 {
+            let (_, evicted) = cache.insert(tag, tag);
+            assert!(evicted.is_none_or(|(_, x)| x != 0));
+        }
+        assert_eq!(*cache.peek(hot), 0);
+    }
+
+    #[test]
+    fn ghost_restores_frequency() {
+        let mut cache = MqSlab::new(1);
+        let (a, _) = cache.insert(0, 'a');
+        cache.get_mut(a);
+        assert_eq!(cache.insert(1, 'b').1, Some((a, 'a')));
+        let (a, _) = cache.insert(0, 'a');
+        assert_eq!(cache.slots[a as usize].queue, Queue::Resident(1));
+        let (b, _) = cache.insert(1, 'b');
+        assert_eq!(cache.slots[b as usize].queue, Queue::Resident(1));
+        let (c, _) = cache.insert(2, 'c');
+        assert_eq!(cache.slots[c as usize].queue, Queue::Resident(0));
+    }
+}
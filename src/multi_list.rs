@@ -0,0 +1,156 @@
+//! Slot storage shared by several independent LRU lists
+
+use alloc::boxed::Box;
+
+use crate::list::{Link, Links, List};
+
+/// A growable slab whose occupied slots each belong to one of several LRU lists
+#[derive(Clone)]
+pub(crate) struct MultiList<T> {
+    slots: Box<[Slot<T>]>,
+    /// Most recently used first
+    lists: Box<[List]>,
+    free: List,
+}
+
+impl<T> MultiList<T> {
+    pub(crate) fn with_capacity(lists: u32, capacity: u32) -> Self {
+        assert!(capacity != u32::MAX, "capacity too large");
+        let mut result = Self {
+            slots: Box::new([]),
+            lists: (0..lists).map(|_| List::EMPTY).collect(),
+            free: List::EMPTY,
+        };
+        result.grow(capacity);
+        result
+    }
+
+    pub(crate) fn len(&self) -> u32 {
+        self.slots.len() as u32 - self.free.len
+    }
+
+    pub(crate) fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    pub(crate) fn lists(&self) -> u32 {
+        self.lists.len() as u32
+    }
+
+    /// Append a new empty list, returning its index
+    pub(crate) fn add_list(&mut self) -> u32 {
+        let index = self.lists();
+        assert!(index != u32::MAX, "too many lists");
+        self.lists = self
+            .lists
+            .iter()
+            .copied()
+            .chain(core::iter::once(List::EMPTY))
+            .collect();
+        index
+    }
+
+    pub(crate) fn len_of(&self, list: u32) -> u32 {
+        self.lists[list as usize].len
+    }
+
+    pub(crate) fn lru_of(&self, list: u32) -> Option<u32> {
+        self.lists[list as usize].back()
+    }
+
+    /// Least recently used slot of the lowest-indexed nonempty list
+    pub(crate) fn lru(&self) -> Option<u32> {
+        self.lists.iter().find_map(|x| x.back())
+    }
+
+    /// Store `value` as the most recently used slot of `list`
+    pub(crate) fn insert(&mut self, list: u32, value: T) -> u32 {
+        assert!(list < self.lists(), "no such list");
+        if self.free.is_empty() {
+            let len = self.capacity();
+            self.grow(2 * len.max(2));
+        }
+        let slot = self.free.pop_front(&mut self.slots[..]).unwrap();
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        entry.value = Some(value);
+        entry.list = list;
+        self.lists[list as usize].push_front(&mut self.slots[..], slot);
+        slot
+    }
+
+    /// The list `slot` belongs to
+    pub(crate) fn list_of(&self, slot: u32) -> u32 {
+        let entry = &self.slots[slot as usize];
+        assert!(entry.value.is_some(), "accessing empty slot");
+        entry.list
+    }
+
+    /// Move `slot` to the front of `list`
+    pub(crate) fn move_to(&mut self, slot: u32, list: u32) {
+        assert!(list < self.lists(), "no such list");
+        let old = self.list_of(slot);
+        self.lists[old as usize].remove(&mut self.slots[..], slot);
+        self.slots[slot as usize].list = list;
+        self.lists[list as usize].push_front(&mut self.slots[..], slot);
+    }
+
+    /// Mark `slot` as the most recently used in its list
+    pub(crate) fn freshen(&mut self, slot: u32) {
+        let list = &mut self.lists[self.list_of(slot) as usize];
+        list.remove(&mut self.slots[..], slot);
+        list.push_front(&mut self.slots[..], slot);
+    }
+
+    pub(crate) fn remove(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        let value = entry.value.take().expect("removing empty slot");
+        let list = entry.list;
+        self.lists[list as usize].remove(&mut self.slots[..], slot);
+        self.free.push_front(&mut self.slots[..], slot);
+        value
+    }
+
+    pub(crate) fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    pub(crate) fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Reallocate to hold `capacity` slots, adding the new ones to the free list
+    fn grow(&mut self, capacity: u32) {
+        let len = self.capacity();
+        self.slots = self
+            .slots
+            .iter_mut()
+            .map(|x| Slot {
+                value: x.value.take(),
+                list: x.list,
+                link: x.link,
+            })
+            .chain((len..capacity).map(|_| Slot {
+                value: None,
+                list: 0,
+                link: Link::UNLINKED,
+            }))
+            .collect();
+        for slot in len..capacity {
+            self.free.push_back(&mut self.slots[..], slot);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Slot<T> {
+    value: Option<T>,
+    list: u32,
+    link: Link,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
@@ -0,0 +1,65 @@
+//! Parallel iteration over an [`LruSlab`] with rayon
+
+use rayon::prelude::*;
+
+use allocator_api2::alloc::Allocator;
+
+use crate::{bitset::Bits, LruSlab};
+
+impl<T: Sync, A: Allocator> LruSlab<T, A> {
+    /// Walk the container in parallel, in slot order
+    ///
+    /// Recency is ignored so that the work splits evenly.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (u32, &T)> + '_ {
+        let values = &self.values[..];
+        self.occupied
+            .words()
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(move |(i, &word)| {
+                // Safety: occupied slots are initialized
+                Bits::new(i, word)
+                    .map(move |slot| (slot, unsafe { values[slot as usize].assume_init_ref() }))
+            })
+    }
+}
+
+impl<T: Send, A: Allocator> LruSlab<T, A> {
+    /// Walk the container uniquely in parallel, in slot order
+    ///
+    /// Recency is ignored so that the work splits evenly.
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (u32, &mut T)> + '_ {
+        self.values
+            .par_chunks_mut(64)
+            .zip(self.occupied.words())
+            .enumerate()
+            .flat_map_iter(|(i, (values, &word))| {
+                let base = i as u32 * 64;
+                values
+                    .iter_mut()
+                    .enumerate()
+                    .filter(move |&(bit, _)| word & (1 << bit) != 0)
+                    // Safety: occupied slots are initialized
+                    .map(move |(bit, value)| {
+                        (base + bit as u32, unsafe { value.assume_init_mut() })
+                    })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn slot_order() {
+        let mut slab = LruSlab::new();
+        let slots = [1, 2, 3].map(|x| slab.insert(x));
+        slab.remove(slots[1]);
+        slab.par_iter_mut().for_each(|(_, x)| *x *= 10);
+        let entries = slab.par_iter().map(|(s, &x)| (s, x)).collect::<Vec<_>>();
+        assert_eq!(entries, [(slots[0], 10), (slots[2], 30)]);
+    }
+}
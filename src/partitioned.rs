@@ -0,0 +1,126 @@
+//! Independent LRU lists sharing one slot arena
+
+use crate::multi_list::MultiList;
+
+/// A random-access table whose entries are divided among partitions, each with its own LRU order
+///
+/// All partitions draw slots from the same storage, so a partition can be capped and evicted
+/// independently, e.g. per tenant, without allocating separately for each.
+#[derive(Clone)]
+pub struct PartitionedSlab<T> {
+    inner: MultiList<T>,
+}
+
+impl<T> PartitionedSlab<T> {
+    /// Create an empty [`PartitionedSlab`] with no partitions
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a [`PartitionedSlab`] that can store at least `capacity` elements without
+    /// reallocating
+    pub fn with_capacity(capacity: u32) -> Self {
+        Self {
+            inner: MultiList::with_capacity(0, capacity),
+        }
+    }
+
+    /// Create a new empty partition, returning its identifier
+    ///
+    /// Partitions are numbered consecutively from 0.
+    pub fn add_partition(&mut self) -> u32 {
+        self.inner.add_list()
+    }
+
+    /// Number of partitions
+    pub fn partitions(&self) -> u32 {
+        self.inner.lists()
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements stored across all partitions
+    pub fn len(&self) -> u32 {
+        self.inner.len()
+    }
+
+    /// Number of elements stored in `partition`
+    pub fn len_of(&self, partition: u32) -> u32 {
+        self.inner.len_of(partition)
+    }
+
+    /// Number of elements that can be stored without reallocating
+    pub fn capacity(&self) -> u32 {
+        self.inner.capacity()
+    }
+
+    /// Insert a value in `partition`, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used in its partition.
+    pub fn insert_in(&mut self, partition: u32, value: T) -> u32 {
+        self.inner.insert(partition, value)
+    }
+
+    /// Get the least recently used slot of `partition`, if any
+    pub fn lru_of(&self, partition: u32) -> Option<u32> {
+        self.inner.lru_of(partition)
+    }
+
+    /// The partition `slot` belongs to
+    pub fn partition(&self, slot: u32) -> u32 {
+        self.inner.list_of(slot)
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        self.inner.remove(slot)
+    }
+
+    /// Mark `slot` as the most recently used in its partition and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        self.inner.freshen(slot);
+        self.inner.peek_mut(slot)
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> &T {
+        self.inner.peek(slot)
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.inner.peek_mut(slot)
+    }
+}
+
+impl<T> Default for PartitionedSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn independent_order() {
+        let mut slab = PartitionedSlab::new();
+        let p = slab.add_partition();
+        let q = slab.add_partition();
+        let a = slab.insert_in(p, 'a');
+        let b = slab.insert_in(q, 'b');
+        slab.insert_in(p, 'c');
+        slab.insert_in(q, 'd');
+        assert_eq!(slab.len_of(p), 2);
+        assert_eq!(slab.lru_of(p), Some(a));
+        assert_eq!(slab.lru_of(q), Some(b));
+        slab.get_mut(b);
+        assert_eq!(slab.remove(slab.lru_of(q).unwrap()), 'd');
+        assert_eq!(slab.partition(b), q);
+        assert_eq!(slab.len(), 3);
+    }
+}
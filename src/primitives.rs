@@ -0,0 +1,22 @@
+//! Synchronization primitives, replaced by loom's models when built with `--cfg loom`
+
+#[cfg(loom)]
+pub(crate) use loom::{
+    sync::{
+        atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    thread_local,
+};
+
+#[cfg(all(not(loom), target_has_atomic = "64"))]
+pub(crate) use core::sync::atomic::AtomicU64;
+#[cfg(all(not(loom), any(feature = "std", target_has_atomic = "64")))]
+pub(crate) use core::sync::atomic::Ordering;
+#[cfg(all(not(loom), feature = "std"))]
+pub(crate) use core::sync::atomic::{AtomicU32, AtomicUsize};
+#[cfg(all(not(loom), feature = "std"))]
+pub(crate) use std::{
+    sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    thread_local,
+};
@@ -0,0 +1,123 @@
+//! LRU tracking within priority classes
+
+use crate::multi_list::MultiList;
+
+/// A random-access table that maintains a separate LRU list for each of a few priority classes
+///
+/// [`lru`](Self::lru) yields the least recently used entry of the lowest nonempty class, so
+/// entries in higher classes are protected from eviction for as long as lower classes have
+/// entries, without being exempt entirely.
+#[derive(Clone)]
+pub struct PrioritySlab<T> {
+    inner: MultiList<T>,
+}
+
+impl<T> PrioritySlab<T> {
+    /// Create an empty [`PrioritySlab`] with classes numbered from 0 to `classes - 1`
+    pub fn new(classes: u8) -> Self {
+        Self::with_capacity(classes, 0)
+    }
+
+    /// Create a [`PrioritySlab`] that can store at least `capacity` elements without reallocating
+    pub fn with_capacity(classes: u8, capacity: u32) -> Self {
+        assert!(classes != 0, "classes must be nonzero");
+        Self {
+            inner: MultiList::with_capacity(classes.into(), capacity),
+        }
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.inner.len()
+    }
+
+    /// Number of elements stored in `class`
+    pub fn len_of(&self, class: u8) -> u32 {
+        self.inner.len_of(class.into())
+    }
+
+    /// Number of elements that can be stored without reallocating
+    pub fn capacity(&self) -> u32 {
+        self.inner.capacity()
+    }
+
+    /// Insert a value in `class`, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used in its class.
+    pub fn insert(&mut self, class: u8, value: T) -> u32 {
+        self.inner.insert(class.into(), value)
+    }
+
+    /// Get the least recently used slot of the lowest nonempty class, if any
+    pub fn lru(&self) -> Option<u32> {
+        self.inner.lru()
+    }
+
+    /// Get the least recently used slot of `class`, if any
+    pub fn lru_of(&self, class: u8) -> Option<u32> {
+        self.inner.lru_of(class.into())
+    }
+
+    /// Remove the element that [`lru`](Self::lru) would return, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        let slot = self.lru()?;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// The class `slot` belongs to
+    pub fn class(&self, slot: u32) -> u8 {
+        self.inner.list_of(slot) as u8
+    }
+
+    /// Move `slot` into `class`, marking it as the most recently used there
+    pub fn set_class(&mut self, slot: u32, class: u8) {
+        self.inner.move_to(slot, class.into());
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        self.inner.remove(slot)
+    }
+
+    /// Mark `slot` as the most recently used in its class and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        self.inner.freshen(slot);
+        self.inner.peek_mut(slot)
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> &T {
+        self.inner.peek(slot)
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.inner.peek_mut(slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowest_class_first() {
+        let mut slab = PrioritySlab::new(4);
+        let a = slab.insert(2, 'a');
+        let b = slab.insert(0, 'b');
+        let c = slab.insert(0, 'c');
+        let d = slab.insert(3, 'd');
+        slab.get_mut(b);
+        assert_eq!(slab.pop_lru(), Some((c, 'c')));
+        assert_eq!(slab.pop_lru(), Some((b, 'b')));
+        slab.set_class(a, 3);
+        assert_eq!(slab.pop_lru(), Some((d, 'd')));
+        assert_eq!(slab.pop_lru(), Some((a, 'a')));
+        assert!(slab.is_empty());
+    }
+}
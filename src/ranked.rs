@@ -0,0 +1,243 @@
+//! LRU tracking with logarithmic-time recency rank queries
+
+use alloc::vec::Vec;
+
+use crate::{LruSlab, NONE};
+
+/// An [`LruSlab`] that also answers recency rank queries in logarithmic time
+///
+/// Every use stamps the slot with the next value of a counter, and a Fenwick tree over stamps
+/// counts the slots used since any given stamp. When the counter runs past the end of the tree,
+/// stamps are reassigned from scratch, which takes linear time but happens at most once every
+/// `len` uses.
+pub struct RankedLruSlab<T> {
+    slab: LruSlab<T>,
+    /// Stamp of each occupied slot
+    stamps: Vec<u32>,
+    /// Slot stamped with each stamp, or `NONE` if that stamp has been superseded
+    slots: Vec<u32>,
+    /// One-based Fenwick tree counting the live stamps
+    tree: Vec<u32>,
+    next_stamp: u32,
+}
+
+impl<T> RankedLruSlab<T> {
+    /// Create an empty [`RankedLruSlab`]
+    pub fn new() -> Self {
+        Self {
+            slab: LruSlab::new(),
+            stamps: Vec::new(),
+            slots: Vec::new(),
+            tree: alloc::vec![0],
+            next_stamp: 0,
+        }
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.slab.is_empty()
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.slab.len()
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&mut self, value: T) -> u32 {
+        let slot = self.slab.insert(value);
+        if slot as usize >= self.stamps.len() {
+            self.stamps.resize(self.slab.capacity() as usize, NONE);
+        }
+        self.stamp(slot);
+        slot
+    }
+
+    /// Get the least recently used slot, if any
+    pub fn lru(&self) -> Option<u32> {
+        self.slab.lru()
+    }
+
+    /// Remove the least recently used element, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        let slot = self.lru()?;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let value = self.slab.remove(slot);
+        self.unstamp(slot);
+        value
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        self.slab.get_mut(slot);
+        self.stamp(slot);
+        self.slab.peek_mut(slot)
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u32) -> Option<&T> {
+        self.slab.get(slot)
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slab.peek(slot)
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slab.peek_mut(slot)
+    }
+
+    /// Number of slots more recently used than `slot`, if it's occupied
+    ///
+    /// The most recently used slot has rank 0.
+    pub fn rank_of(&self, slot: u32) -> Option<u32> {
+        if !self.slab.contains(slot) {
+            return None;
+        }
+        Some(self.len() - self.prefix(self.stamps[slot as usize]))
+    }
+
+    /// The slot with `n` slots more recently used than it, if any
+    pub fn nth_mru(&self, n: u32) -> Option<u32> {
+        (n < self.len()).then(|| self.select(self.len() - n))
+    }
+
+    /// The slot with `n` slots less recently used than it, if any
+    pub fn nth_lru(&self, n: u32) -> Option<u32> {
+        (n < self.len()).then(|| self.select(n + 1))
+    }
+
+    /// The underlying slab
+    ///
+    /// Unique access isn't offered, since reordering elements would invalidate the ranks.
+    pub fn slab(&self) -> &LruSlab<T> {
+        &self.slab
+    }
+
+    /// Give `slot` the newest stamp, superseding any it already has
+    fn stamp(&mut self, slot: u32) {
+        self.unstamp(slot);
+        if self.next_stamp as usize == self.slots.len() {
+            self.restamp();
+            return;
+        }
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.stamps[slot as usize] = stamp;
+        self.slots[stamp as usize] = slot;
+        self.add(stamp, 1);
+    }
+
+    /// Supersede the stamp of `slot`, if any
+    fn unstamp(&mut self, slot: u32) {
+        let stamp = self.stamps[slot as usize];
+        if self.slots.get(stamp as usize) == Some(&slot) {
+            self.slots[stamp as usize] = NONE;
+            self.stamps[slot as usize] = NONE;
+            self.add(stamp, 0u32.wrapping_sub(1));
+        }
+    }
+
+    /// Stamp every occupied slot afresh, in recency order
+    fn restamp(&mut self) {
+        let size = (2 * self.len() as usize).max(16);
+        self.slots.clear();
+        self.slots.resize(size, NONE);
+        self.tree.clear();
+        self.tree.resize(size + 1, 0);
+        for (stamp, (slot, _)) in (0..).zip(self.slab.iter().rev()) {
+            self.stamps[slot as usize] = stamp;
+            self.slots[stamp as usize] = slot;
+            self.tree[stamp as usize + 1] = 1;
+        }
+        // Build the tree in place in linear time
+        for i in 1..=size {
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= size {
+                self.tree[parent] += self.tree[i];
+            }
+        }
+        self.next_stamp = self.len();
+    }
+
+    /// Add `delta`, modulo 2^32, to the count of `stamp`
+    fn add(&mut self, stamp: u32, delta: u32) {
+        let mut i = stamp as usize + 1;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i].wrapping_add(delta);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Number of live stamps no newer than `stamp`
+    fn prefix(&self, stamp: u32) -> u32 {
+        let mut i = stamp as usize + 1;
+        let mut sum = 0;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+
+    /// Slot with the `k`th oldest live stamp, counting from 1
+    fn select(&self, mut k: u32) -> u32 {
+        let size = self.tree.len() - 1;
+        let mut position = 0;
+        let mut step = size.checked_ilog2().map_or(0, |x| 1 << x);
+        while step != 0 {
+            let next = position + step;
+            if next <= size && self.tree[next] < k {
+                position = next;
+                k -= self.tree[next];
+            }
+            step >>= 1;
+        }
+        self.slots[position]
+    }
+}
+
+impl<T> Default for RankedLruSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn matches_linear_rank() {
+        let mut rng = Rng::new();
+        let mut slab = RankedLruSlab::new();
+        let mut live = Vec::new();
+        for _ in 0..2000 {
+            match rng.below(4) {
+                0 | 1 => live.push(slab.insert(())),
+                2 if !live.is_empty() => {
+                    let slot = live.swap_remove(rng.below(live.len() as u32) as usize);
+                    slab.remove(slot);
+                }
+                _ if !live.is_empty() => {
+                    slab.get_mut(live[rng.below(live.len() as u32) as usize]);
+                }
+                _ => {}
+            }
+            for (rank, (slot, _)) in (0..).zip(slab.slab().iter()) {
+                assert_eq!(slab.rank_of(slot), Some(rank));
+                assert_eq!(slab.nth_mru(rank), Some(slot));
+                assert_eq!(slab.nth_lru(slab.len() - 1 - rank), Some(slot));
+            }
+        }
+    }
+}
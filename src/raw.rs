@@ -0,0 +1,132 @@
+//! Conversion of an [`LruSlab`] to and from its underlying representation
+
+use alloc::vec::Vec;
+
+use allocator_api2::alloc::Allocator;
+
+use crate::{list::Link, InvariantError, LruSlab, FREE};
+
+/// The underlying representation of an [`LruSlab`], for custom serialization and migration
+///
+/// Slot indices are positions in `slots`, and `u32::MAX` stands for no slot. Occupied slots form
+/// the LRU list from `head` to `tail` through their `next` and `prev` links, while vacant slots
+/// form the free list from `free` through their `next` links alone.
+pub struct RawParts<T> {
+    /// Every slot, occupied or not; the length is the capacity
+    pub slots: Vec<RawSlot<T>>,
+    /// Most recently used slot
+    pub head: u32,
+    /// Least recently used slot
+    pub tail: u32,
+    /// First slot of the free list
+    pub free: u32,
+    /// Number of occupied slots
+    pub len: u32,
+}
+
+/// A slot of an [`LruSlab`], as represented in [`RawParts`]
+pub struct RawSlot<T> {
+    /// The element stored, if the slot is occupied
+    pub value: Option<T>,
+    /// Next more recently used slot; ignored for vacant slots
+    pub prev: u32,
+    /// Next less recently used slot, or next slot of the free list
+    pub next: u32,
+    /// Number of outstanding pins; must be zero for vacant slots
+    pub pins: u32,
+}
+
+impl<T, A: Allocator + Clone> LruSlab<T, A> {
+    /// Decompose into the underlying representation, applying any recorded accesses first
+    pub fn into_raw_parts(mut self) -> RawParts<T> {
+        self.flush();
+        let slots = (0..self.capacity())
+            .map(|slot| {
+                let Link { prev, next } = self.links[slot as usize];
+                let value = self.is_occupied(slot).then(|| {
+                    self.occupied.remove(slot);
+                    // Safety: the slot was occupied, and won't be dropped now that it isn't
+                    unsafe { self.values[slot as usize].assume_init_read() }
+                });
+                RawSlot {
+                    value,
+                    prev,
+                    next,
+                    pins: self.meta[slot as usize].pins,
+                }
+            })
+            .collect();
+        RawParts {
+            slots,
+            head: self.head,
+            tail: self.tail,
+            free: self.free,
+            len: self.len,
+        }
+    }
+}
+
+impl<T> LruSlab<T> {
+    /// Reconstruct an [`LruSlab`] from parts returned by
+    /// [`into_raw_parts`](Self::into_raw_parts), or equivalent
+    ///
+    /// Fails if the parts are inconsistent, as determined by
+    /// [`check_invariants`](Self::check_invariants).
+    pub fn from_raw_parts(parts: RawParts<T>) -> Result<Self, InvariantError> {
+        let capacity = u32::try_from(parts.slots.len())
+            .ok()
+            .filter(|&x| x < FREE)
+            .ok_or(InvariantError {
+                slot: None,
+                reason: "capacity too large",
+            })?;
+        let mut slab = Self::with_capacity(capacity);
+        for (slot, raw) in (0..).zip(parts.slots) {
+            slab.meta[slot as usize].pins = raw.pins;
+            slab.links[slot as usize] = match raw.value {
+                Some(value) => {
+                    slab.values[slot as usize].write(value);
+                    slab.occupied.insert(slot);
+                    Link {
+                        prev: raw.prev,
+                        next: raw.next,
+                    }
+                }
+                None => Link {
+                    prev: FREE,
+                    next: raw.next,
+                },
+            };
+        }
+        slab.head = parts.head;
+        slab.tail = parts.tail;
+        slab.free = parts.free;
+        slab.len = parts.len;
+        slab.peak_len = parts.len;
+
+        slab.check_invariants()?;
+        Ok(slab)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut slab = (0..10).collect::<LruSlab<_>>();
+        slab.remove(4);
+        slab.get_mut(2);
+        slab.pin(7);
+        let order = slab.iter().map(|(s, &x)| (s, x)).collect::<Vec<_>>();
+        let slab = LruSlab::from_raw_parts(slab.into_raw_parts()).unwrap();
+        assert_eq!(slab.iter().map(|(s, &x)| (s, x)).collect::<Vec<_>>(), order);
+        assert!(slab.is_pinned(7));
+
+        let mut parts = slab.into_raw_parts();
+        parts.tail = parts.head;
+        let err = LruSlab::from_raw_parts(parts).err().unwrap();
+        assert_eq!(err.reason, "tail isn't the end of the LRU list");
+    }
+}
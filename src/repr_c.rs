@@ -0,0 +1,128 @@
+//! A `repr(C)` mirror of [`LruSlab`], for passing slabs between separately compiled binaries
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{mem::MaybeUninit, ptr};
+
+use crate::{InvariantError, LruSlab, RawParts, RawSlot};
+
+/// An [`LruSlab`] in a layout that's fixed regardless of compiler version or settings
+///
+/// Converting to and from [`LruSlab`] takes time proportional to the capacity. The storage is
+/// released through a function pointer supplied by the binary that allocated it, so it can be
+/// dropped on either side of a plugin boundary. `T` must itself have a stable layout for the
+/// result to be meaningful. Slots, recency, and pins are preserved.
+#[repr(C)]
+pub struct ReprCSlab<T> {
+    slots: *mut ReprCSlot<T>,
+    capacity: usize,
+    head: u32,
+    tail: u32,
+    free: u32,
+    len: u32,
+    release: unsafe extern "C" fn(*mut ReprCSlot<T>, usize),
+}
+
+/// A slot of a [`ReprCSlab`]
+#[repr(C)]
+struct ReprCSlot<T> {
+    prev: u32,
+    next: u32,
+    pins: u32,
+    /// Nonzero if `value` is initialized
+    occupied: u8,
+    value: MaybeUninit<T>,
+}
+
+// Safety: owns its elements uniquely
+unsafe impl<T: Send> Send for ReprCSlab<T> {}
+// Safety: exposes no shared mutation
+unsafe impl<T: Sync> Sync for ReprCSlab<T> {}
+
+impl<T> ReprCSlab<T> {
+    /// Reconstruct an [`LruSlab`], failing if the slab was corrupted in transit
+    pub fn into_slab(self) -> Result<LruSlab<T>, InvariantError> {
+        // Safety: `slots` and `capacity` describe a live allocation owned by `self`
+        let slots = unsafe { core::slice::from_raw_parts_mut(self.slots, self.capacity) };
+        let slots = slots
+            .iter_mut()
+            .map(|slot| RawSlot {
+                // Safety: `occupied` is set only for initialized values, and cleared here so that
+                // moved values won't be dropped again
+                value: (slot.occupied != 0).then(|| {
+                    slot.occupied = 0;
+                    unsafe { slot.value.assume_init_read() }
+                }),
+                prev: slot.prev,
+                next: slot.next,
+                pins: slot.pins,
+            })
+            .collect::<Vec<_>>();
+        LruSlab::from_raw_parts(RawParts {
+            slots,
+            head: self.head,
+            tail: self.tail,
+            free: self.free,
+            len: self.len,
+        })
+    }
+}
+
+impl<T> From<LruSlab<T>> for ReprCSlab<T> {
+    fn from(slab: LruSlab<T>) -> Self {
+        let parts = slab.into_raw_parts();
+        let slots = parts
+            .slots
+            .into_iter()
+            .map(|slot| ReprCSlot {
+                prev: slot.prev,
+                next: slot.next,
+                pins: slot.pins,
+                occupied: slot.value.is_some().into(),
+                value: slot.value.map_or(MaybeUninit::uninit(), MaybeUninit::new),
+            })
+            .collect::<Box<[_]>>();
+        let capacity = slots.len();
+        Self {
+            slots: Box::into_raw(slots).cast(),
+            capacity,
+            head: parts.head,
+            tail: parts.tail,
+            free: parts.free,
+            len: parts.len,
+            release: release::<T>,
+        }
+    }
+}
+
+impl<T> Drop for ReprCSlab<T> {
+    fn drop(&mut self) {
+        // Safety: `release` was supplied alongside `slots` by the binary that allocated them
+        unsafe { (self.release)(self.slots, self.capacity) }
+    }
+}
+
+/// Drop the occupied values of `slots` and free them
+unsafe extern "C" fn release<T>(slots: *mut ReprCSlot<T>, capacity: usize) {
+    let mut slots = Box::from_raw(ptr::slice_from_raw_parts_mut(slots, capacity));
+    for slot in &mut slots[..] {
+        if slot.occupied != 0 {
+            slot.value.assume_init_drop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut slab = (0..4).map(alloc::rc::Rc::new).collect::<LruSlab<_>>();
+        slab.remove(2);
+        slab.get_mut(1);
+        let order = slab.iter().map(|(_, x)| **x).collect::<Vec<_>>();
+        let slab = ReprCSlab::from(slab).into_slab().unwrap();
+        assert_eq!(slab.iter().map(|(_, x)| **x).collect::<Vec<_>>(), order);
+        drop(ReprCSlab::from(slab));
+    }
+}
@@ -0,0 +1,282 @@
+//! The S3-FIFO replacement algorithm, built from three static FIFO queues
+
+use alloc::boxed::Box;
+
+use crate::{
+    list::{Link, Links, List},
+    tag_index::TagIndex,
+};
+
+/// A fixed-capacity table that evicts according to the S3-FIFO policy
+///
+/// New entries enter a small FIFO queue. Entries that are used at least twice before reaching its
+/// end move to a larger main FIFO queue, while the rest are evicted, leaving ghosts in a third
+/// queue. Inserting a tag that still has a ghost skips the small queue. Entries in the main queue
+/// are reinserted instead of evicted while they have been used since the last time they reached
+/// its end. Accesses only bump a small counter, so hits never move entries.
+///
+/// Tags are supplied by the caller on insert and should identify the value, e.g. by hashing its
+/// key. Ghosts occupy slots without storing values.
+pub struct S3FifoSlab<T> {
+    slots: Box<[Slot<T>]>,
+    free: List,
+    /// Probationary entries, newest first
+    small: List,
+    /// Entries that proved themselves in `small`, or returned as ghosts, newest first
+    main: List,
+    /// Nonresident entries evicted from `small`, newest first
+    ghost_list: List,
+    /// Locates ghosts by tag
+    ghosts: TagIndex,
+    capacity: u32,
+    small_capacity: u32,
+    main_capacity: u32,
+    ghost_capacity: u32,
+}
+
+impl<T> S3FifoSlab<T> {
+    /// Create an [`S3FifoSlab`] that holds at most `capacity` values
+    ///
+    /// Ten percent of the capacity, and at least one slot, is devoted to the small queue. As many
+    /// ghosts as there are slots in the main queue are retained.
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        let small_capacity = (capacity / 10).max(1);
+        let main_capacity = capacity - small_capacity;
+        let ghost_capacity = main_capacity.max(1);
+        let len = capacity
+            .checked_add(ghost_capacity)
+            .filter(|&x| x != u32::MAX)
+            .expect("capacity too large");
+        let mut slots = (0..len)
+            .map(|_| Slot {
+                value: None,
+                tag: 0,
+                frequency: 0,
+                queue: Queue::Free,
+                link: Link::UNLINKED,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..len {
+            free.push_back(&mut slots[..], slot);
+        }
+        Self {
+            slots,
+            free,
+            small: List::EMPTY,
+            main: List::EMPTY,
+            ghost_list: List::EMPTY,
+            ghosts: TagIndex::new(ghost_capacity),
+            capacity,
+            small_capacity,
+            main_capacity,
+            ghost_capacity,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.small.len + self.main.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Insert a value identified by `tag`, returning the slot it was stored in and the slot and
+    /// value of any entry evicted to make room
+    pub fn insert(&mut self, tag: u64, value: T) -> (u32, Option<(u32, T)>) {
+        // Detach any ghost first so that making room can't discard it
+        let ghost = self.ghosts.remove(tag);
+        if let Some(ghost) = ghost {
+            self.ghost_list.remove(&mut self.slots[..], ghost);
+        }
+
+        let evicted = match self.len() == self.capacity {
+            true => Some(self.evict()),
+            false => None,
+        };
+
+        let (slot, queue) = match ghost {
+            Some(slot) => (slot, Queue::Main),
+            None => {
+                let slot = self
+                    .free
+                    .pop_front(&mut self.slots[..])
+                    .expect("no free slots");
+                (slot, Queue::Small)
+            }
+        };
+        let entry = &mut self.slots[slot as usize];
+        entry.value = Some(value);
+        entry.tag = tag;
+        entry.frequency = 0;
+        entry.queue = queue;
+        match queue {
+            Queue::Main => self.main.push_front(&mut self.slots[..], slot),
+            _ => self.small.push_front(&mut self.slots[..], slot),
+        }
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    ///
+    /// No ghost is left behind.
+    pub fn remove(&mut self, slot: u32) -> T {
+        match self.slots[slot as usize].queue {
+            Queue::Small => self.small.remove(&mut self.slots[..], slot),
+            Queue::Main => self.main.remove(&mut self.slots[..], slot),
+            _ => panic!("removing empty slot"),
+        }
+        let value = self.slots[slot as usize].value.take().unwrap();
+        self.release(slot);
+        value
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &mut self.slots[slot as usize];
+        entry.frequency = (entry.frequency + 1).min(MAX_FREQUENCY);
+        entry.value.as_mut().unwrap()
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    fn evict(&mut self) -> (u32, T) {
+        if self.small.len >= self.small_capacity || self.main.is_empty() {
+            if let Some(evicted) = self.evict_small() {
+                return evicted;
+            }
+        }
+        self.evict_main()
+    }
+
+    /// Evict from the end of the small queue, promoting entries that were used more than once
+    ///
+    /// Returns `None` if every entry was promoted without the main queue overflowing.
+    fn evict_small(&mut self) -> Option<(u32, T)> {
+        while let Some(slot) = self.small.pop_back(&mut self.slots[..]) {
+            let entry = &mut self.slots[slot as usize];
+            if entry.frequency > 1 {
+                entry.queue = Queue::Main;
+                self.main.push_front(&mut self.slots[..], slot);
+                if self.main.len > self.main_capacity {
+                    return Some(self.evict_main());
+                }
+                continue;
+            }
+            entry.queue = Queue::Ghost;
+            let value = entry.value.take().unwrap();
+            if let Some(stale) = self.ghosts.insert(entry.tag, slot) {
+                // The same tag was evicted before; only the newest ghost is meaningful
+                self.ghost_list.remove(&mut self.slots[..], stale);
+                self.release(stale);
+            }
+            self.ghost_list.push_front(&mut self.slots[..], slot);
+            if self.ghost_list.len > self.ghost_capacity {
+                let oldest = self.ghost_list.pop_back(&mut self.slots[..]).unwrap();
+                self.ghosts.remove(self.slots[oldest as usize].tag);
+                self.release(oldest);
+            }
+            return Some((slot, value));
+        }
+        None
+    }
+
+    /// Evict from the end of the main queue, reinserting entries that were used since they were
+    /// last there
+    fn evict_main(&mut self) -> (u32, T) {
+        loop {
+            let slot = self
+                .main
+                .pop_back(&mut self.slots[..])
+                .expect("evicting from empty table");
+            let entry = &mut self.slots[slot as usize];
+            if entry.frequency == 0 {
+                let value = entry.value.take().unwrap();
+                self.release(slot);
+                return (slot, value);
+            }
+            entry.frequency -= 1;
+            self.main.push_front(&mut self.slots[..], slot);
+        }
+    }
+
+    /// Return an unlinked slot to the free list
+    fn release(&mut self, slot: u32) {
+        self.slots[slot as usize].queue = Queue::Free;
+        self.free.push_front(&mut self.slots[..], slot);
+    }
+}
+
+/// Saturation point of [`Slot::frequency`]
+const MAX_FREQUENCY: u8 = 3;
+
+struct Slot<T> {
+    value: Option<T>,
+    tag: u64,
+    /// Uses since insertion, or since last reinserted into the main queue
+    frequency: u8,
+    queue: Queue,
+    link: Link,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Queue {
+    Free,
+    Small,
+    Main,
+    Ghost,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_hit_wonders_evicted_first() {
+        let mut cache = S3FifoSlab::new(10);
+        let (hot, _) = cache.insert(0, 0);
+        cache.get_mut(hot);
+        cache.get_mut(hot);
+        for tag in 1..100 {
+            let (_, evicted) = cache.insert(tag, tag);
+            assert!(evicted.is_none_or(|(_, x)| x != 0));
+        }
+        assert_eq!(*cache.peek(hot), 0);
+    }
+
+    #[test]
+    fn ghost_enters_main() {
+        let mut cache = S3FifoSlab::new(10);
+        for tag in 0..11 {
+            cache.insert(tag, tag);
+        }
+        // Tag 0 was evicted from the small queue, leaving a ghost
+        let (slot, _) = cache.insert(0, 0);
+        assert_eq!(cache.slots[slot as usize].queue, Queue::Main);
+        let (slot, _) = cache.insert(100, 100);
+        assert_eq!(cache.slots[slot as usize].queue, Queue::Small);
+    }
+}
@@ -0,0 +1,197 @@
+//! Approximate LRU by sampling, in the style of Redis
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{rng::Rng, NONE};
+
+/// A fixed-capacity table that approximates LRU eviction by sampling random slots
+///
+/// Accesses only store the current time in the accessed slot, measured by a clock that ticks on
+/// every insert and use, so no list is maintained. When full, a handful of slots are sampled at
+/// random and merged into a small pool of the oldest candidates seen so far, and the oldest
+/// candidate in the pool that hasn't been used since it was sampled is evicted. Retaining the pool
+/// across evictions brings the results much closer to true LRU than sampling alone.
+pub struct SampledSlab<T> {
+    slots: Box<[Slot<T>]>,
+    len: u32,
+    /// First unused slot
+    free: u32,
+    /// Number of slots examined per eviction
+    samples: u32,
+    /// Eviction candidates as `(stamp, slot)`, oldest first
+    pool: Vec<(u64, u32)>,
+    clock: u64,
+    rng: Rng,
+}
+
+impl<T> SampledSlab<T> {
+    /// Create a [`SampledSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        Self::with_samples(capacity, DEFAULT_SAMPLES)
+    }
+
+    /// Create a [`SampledSlab`] that holds at most `capacity` values and examines `samples` slots
+    /// per eviction
+    pub fn with_samples(capacity: u32, samples: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        assert!(capacity != u32::MAX, "capacity too large");
+        assert!(samples != 0, "samples must be nonzero");
+        Self {
+            slots: (0..capacity)
+                .map(|n| Slot {
+                    value: None,
+                    stamp: 0,
+                    next: if n + 1 == capacity { NONE } else { n + 1 },
+                })
+                .collect(),
+            len: 0,
+            free: 0,
+            samples,
+            pool: Vec::with_capacity(POOL_SIZE),
+            clock: 0,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Insert a value, returning the slot it was stored in and the slot and value of any entry
+    /// evicted to make room
+    ///
+    /// When an entry is evicted, the new value takes over its slot.
+    pub fn insert(&mut self, value: T) -> (u32, Option<(u32, T)>) {
+        let evicted = match self.free {
+            NONE => {
+                let slot = self.victim();
+                Some((slot, self.remove(slot)))
+            }
+            _ => None,
+        };
+        let slot = self.free;
+        let stamp = self.tick();
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        self.free = entry.next;
+        entry.value = Some(value);
+        entry.stamp = stamp;
+        self.len += 1;
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        let value = entry.value.take().expect("removing empty slot");
+        entry.next = self.free;
+        self.free = slot;
+        self.len -= 1;
+        value
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let stamp = self.tick();
+        let entry = &mut self.slots[slot as usize];
+        entry.stamp = stamp;
+        entry.value.as_mut().expect("accessing empty slot")
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Choose a slot to evict, when every slot is occupied
+    fn victim(&mut self) -> u32 {
+        loop {
+            for _ in 0..self.samples {
+                let slot = self.rng.below(self.capacity());
+                self.offer(self.slots[slot as usize].stamp, slot);
+            }
+            while !self.pool.is_empty() {
+                let (stamp, slot) = self.pool.remove(0);
+                // Skip candidates that were used or replaced since they were sampled
+                if self.slots[slot as usize].stamp == stamp {
+                    return slot;
+                }
+            }
+        }
+    }
+
+    /// Add a candidate to the pool if it's older than the youngest there, or there's room
+    fn offer(&mut self, stamp: u64, slot: u32) {
+        let position = self.pool.partition_point(|&(x, _)| x < stamp);
+        if self.pool.get(position) == Some(&(stamp, slot)) {
+            return;
+        }
+        if self.pool.len() == POOL_SIZE {
+            if position == POOL_SIZE {
+                return;
+            }
+            self.pool.pop();
+        }
+        self.pool.insert(position, (stamp, slot));
+    }
+}
+
+/// Number of slots examined per eviction by [`SampledSlab::new`]
+const DEFAULT_SAMPLES: u32 = 5;
+/// Number of eviction candidates retained between evictions
+const POOL_SIZE: usize = 16;
+
+struct Slot<T> {
+    value: Option<T>,
+    /// Value of the clock at the slot's last use
+    stamp: u64,
+    /// Next slot in the free list
+    next: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_retains_old_candidates() {
+        let mut cache = SampledSlab::with_samples(64, 1);
+        let cold = cache.insert(0).0;
+        for x in 1..64 {
+            cache.insert(x);
+        }
+        // Sampling one slot at a time, the old entry is found once and then retained
+        for x in 64..1000 {
+            if cache.insert(x).1 == Some((cold, 0)) {
+                return;
+            }
+            for slot in 0..64 {
+                if slot != cold {
+                    cache.get_mut(slot);
+                }
+            }
+        }
+        panic!("never evicted the least recently used entry");
+    }
+}
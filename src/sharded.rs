@@ -0,0 +1,180 @@
+//! Concurrent LRU tracking split across independently locked shards
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    primitives::{AtomicU64, Mutex, MutexGuard, Ordering},
+    LruSlab,
+};
+
+/// A concurrent table whose slots are divided among several independently locked [`LruSlab`]s
+///
+/// Slot ids encode the shard in their low bits, so operations on different shards never contend.
+/// Recency is tracked exactly within each shard, and [`lru`](Self::lru) approximates the global
+/// victim by comparing a sample of shard tails.
+pub struct ShardedLruSlab<T> {
+    shards: Box<[Mutex<Shard<T>>]>,
+    /// log2 of the number of shards
+    shift: u32,
+    /// Ticks on every insert and use, so that shard tails can be compared
+    clock: AtomicU64,
+}
+
+impl<T> ShardedLruSlab<T> {
+    /// Create an empty [`ShardedLruSlab`] with at least `shards` shards
+    ///
+    /// The shard count is rounded up to a power of two.
+    pub fn new(shards: u32) -> Self {
+        let count = shards.max(1).next_power_of_two();
+        Self {
+            shards: (0..count)
+                .map(|_| {
+                    Mutex::new(Shard {
+                        slab: LruSlab::new(),
+                        stamps: Vec::new(),
+                    })
+                })
+                .collect(),
+            shift: count.trailing_zeros(),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of shards
+    pub fn shards(&self) -> u32 {
+        self.shards.len() as u32
+    }
+
+    /// Number of elements stored across all shards
+    pub fn len(&self) -> u32 {
+        (0..self.shards()).map(|x| self.lock(x).slab.len()).sum()
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert a value into the shard chosen by `hash`, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used in its shard.
+    pub fn insert(&self, hash: u64, value: T) -> u32 {
+        let shard = (hash & (self.shards.len() as u64 - 1)) as u32;
+        let mut guard = self.lock(shard);
+        assert!(
+            u64::from(guard.slab.vacant_key()) << self.shift < u64::from(u32::MAX),
+            "shard full"
+        );
+        let local = guard.slab.insert(value);
+        guard.stamp(local, self.tick());
+        self.compose(shard, local)
+    }
+
+    /// Remove the element stored in `slot`, returning it, if any
+    pub fn remove(&self, slot: u32) -> Option<T> {
+        let (shard, local) = self.decompose(slot);
+        let mut guard = self.lock(shard);
+        guard.slab.contains(local).then(|| guard.slab.remove(local))
+    }
+
+    /// Mark `slot` as the most recently used in its shard and apply `f` to its value, if occupied
+    pub fn get_mut<R>(&self, slot: u32, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let (shard, local) = self.decompose(slot);
+        let mut guard = self.lock(shard);
+        if !guard.slab.contains(local) {
+            return None;
+        }
+        guard.stamp(local, self.tick());
+        Some(f(guard.slab.get_mut(local)))
+    }
+
+    /// Apply `f` to the value in `slot` without marking it as most recently used, if occupied
+    pub fn peek<R>(&self, slot: u32, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let (shard, local) = self.decompose(slot);
+        let guard = self.lock(shard);
+        guard.slab.get(local).map(f)
+    }
+
+    /// Approximate the least recently used slot by comparing the tails of up to `samples` shards
+    ///
+    /// Shards are visited starting from one chosen by `seed`, so callers can spread sampling
+    /// across shards. The result may be stale by the time it's used.
+    pub fn lru(&self, seed: u64, samples: u32) -> Option<u32> {
+        let count = self.shards();
+        let mut best = None;
+        for i in 0..samples.min(count) {
+            let shard = (seed as u32).wrapping_add(i) & (count - 1);
+            let guard = self.lock(shard);
+            let Some(local) = guard.slab.lru() else {
+                continue;
+            };
+            let stamp = guard.stamps[local as usize];
+            if best.is_none_or(|(_, x)| stamp < x) {
+                best = Some((self.compose(shard, local), stamp));
+            }
+        }
+        best.map(|(slot, _)| slot)
+    }
+
+    /// Remove the element that [`lru`](Self::lru) would return, returning its slot and value
+    pub fn pop_lru(&self, seed: u64, samples: u32) -> Option<(u32, T)> {
+        loop {
+            let slot = self.lru(seed, samples)?;
+            // Another thread may have removed it since it was sampled
+            if let Some(value) = self.remove(slot) {
+                return Some((slot, value));
+            }
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn lock(&self, shard: u32) -> MutexGuard<'_, Shard<T>> {
+        self.shards[shard as usize].lock().expect("lock poisoned")
+    }
+
+    fn compose(&self, shard: u32, local: u32) -> u32 {
+        local << self.shift | shard
+    }
+
+    fn decompose(&self, slot: u32) -> (u32, u32) {
+        (slot & ((1 << self.shift) - 1), slot >> self.shift)
+    }
+}
+
+struct Shard<T> {
+    slab: LruSlab<T>,
+    /// Value of the clock at each slot's last use
+    stamps: Vec<u64>,
+}
+
+impl<T> Shard<T> {
+    fn stamp(&mut self, slot: u32, now: u64) {
+        let len = self.slab.capacity() as usize;
+        if self.stamps.len() < len {
+            self.stamps.resize(len, 0);
+        }
+        self.stamps[slot as usize] = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampled_lru() {
+        let slab = ShardedLruSlab::new(4);
+        let slots = (0..8).map(|x| slab.insert(x, x)).collect::<Vec<_>>();
+        assert_eq!(slab.len(), 8);
+        slab.get_mut(slots[0], |_| ());
+        slab.get_mut(slots[1], |_| ());
+        slab.get_mut(slots[1], |_| ());
+        // Shard 0 holds 0 and 4, shard 1 holds 1 and 5; compare their tails
+        assert_eq!(slab.lru(0, 2), Some(slots[4]));
+        assert_eq!(slab.pop_lru(0, 4), Some((slots[2], 2)));
+        assert_eq!(slab.peek(slots[6], |&x| x), Some(6));
+    }
+}
@@ -0,0 +1,174 @@
+//! The SIEVE eviction algorithm
+
+use alloc::boxed::Box;
+
+use crate::{
+    list::{Link, Links, List},
+    NONE,
+};
+
+/// A fixed-capacity table that evicts according to the SIEVE algorithm
+///
+/// Entries are kept in insertion order and never move on access, which only sets a visited bit. To
+/// evict, a hand walks from the oldest entry towards the newest, clearing visited bits, and evicts
+/// the first unvisited entry; it resumes from that position next time. Entries that survive a pass
+/// of the hand stay behind it, so popular entries are retained without the list writes of LRU.
+pub struct SieveSlab<T> {
+    slots: Box<[Slot<T>]>,
+    /// Occupied slots, newest first
+    queue: List,
+    free: List,
+    /// Next slot to be considered for eviction, or `NONE` to start from the oldest entry
+    hand: u32,
+}
+
+impl<T> SieveSlab<T> {
+    /// Create a [`SieveSlab`] that holds at most `capacity` values
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        assert!(capacity != u32::MAX, "capacity too large");
+        let mut slots = (0..capacity)
+            .map(|_| Slot {
+                value: None,
+                visited: false,
+                link: Link::UNLINKED,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..capacity {
+            free.push_back(&mut slots[..], slot);
+        }
+        Self {
+            slots,
+            queue: List::EMPTY,
+            free,
+            hand: NONE,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.queue.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Insert a value, returning the slot it was stored in and the slot and value of any entry
+    /// evicted to make room
+    ///
+    /// When an entry is evicted, the new value takes over its slot.
+    pub fn insert(&mut self, value: T) -> (u32, Option<(u32, T)>) {
+        let (slot, evicted) = match self.free.pop_front(&mut self.slots[..]) {
+            Some(slot) => (slot, None),
+            None => {
+                let slot = self.sweep();
+                (slot, Some((slot, self.unlink(slot))))
+            }
+        };
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        entry.value = Some(value);
+        entry.visited = false;
+        self.queue.push_front(&mut self.slots[..], slot);
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        let value = self.unlink(slot);
+        self.free.push_front(&mut self.slots[..], slot);
+        value
+    }
+
+    /// Mark `slot` as visited and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &mut self.slots[slot as usize];
+        entry.visited = true;
+        entry.value.as_mut().unwrap()
+    }
+
+    /// Access `slot` without marking it as visited
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without marking it as visited
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Move the hand to the next unvisited slot, clearing visited bits along the way
+    fn sweep(&mut self) -> u32 {
+        let mut slot = match self.hand {
+            NONE => self.queue.tail,
+            hand => hand,
+        };
+        loop {
+            let entry = &mut self.slots[slot as usize];
+            if !entry.visited {
+                self.hand = slot;
+                return slot;
+            }
+            entry.visited = false;
+            slot = match entry.link.prev {
+                NONE => self.queue.tail,
+                prev => prev,
+            };
+        }
+    }
+
+    /// Remove an occupied slot from the queue, yielding its value
+    fn unlink(&mut self, slot: u32) -> T {
+        let value = self.slots[slot as usize]
+            .value
+            .take()
+            .expect("removing empty slot");
+        if self.hand == slot {
+            self.hand = self.slots[slot as usize].link.prev;
+        }
+        self.queue.remove(&mut self.slots[..], slot);
+        value
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    /// Whether the slot was accessed since the hand last passed it
+    visited: bool,
+    link: Link,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visited_survive() {
+        let mut cache = SieveSlab::new(3);
+        let (a, _) = cache.insert('a');
+        cache.insert('b');
+        cache.insert('c');
+        cache.get_mut(a);
+        let (_, evicted) = cache.insert('d');
+        assert_eq!(evicted.map(|(_, x)| x), Some('b'));
+        // The hand resumes where it left off, rather than returning to the oldest entry
+        cache.get_mut(a);
+        let (_, evicted) = cache.insert('e');
+        assert_eq!(evicted.map(|(_, x)| x), Some('c'));
+        assert_eq!(*cache.peek(a), 'a');
+    }
+}
@@ -0,0 +1,81 @@
+//! Approximate access frequency counting
+
+use alloc::boxed::Box;
+
+/// A count-min sketch of 4-bit counters that periodically halves every count, so the estimates
+/// reflect recent popularity
+#[derive(Clone)]
+pub(crate) struct FrequencySketch {
+    /// Sixteen 4-bit counters per word
+    table: Box<[u64]>,
+    additions: u32,
+    /// Number of additions after which counts are halved
+    sample_size: u32,
+}
+
+impl FrequencySketch {
+    /// Create a sketch suitable for estimating the frequencies of about `capacity` distinct items
+    pub(crate) fn new(capacity: u32) -> Self {
+        let words = (capacity as usize).next_power_of_two().max(4);
+        Self {
+            table: (0..words).map(|_| 0).collect(),
+            additions: 0,
+            sample_size: capacity.saturating_mul(10).max(16),
+        }
+    }
+
+    /// Estimated number of recent occurrences of `hash`, saturating at 15
+    pub(crate) fn frequency(&self, hash: u64) -> u32 {
+        (0..ROWS)
+            .map(|row| {
+                let (word, shift) = self.counter(hash, row);
+                ((self.table[word] >> shift) & 0xF) as u32
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Record an occurrence of `hash`
+    pub(crate) fn increment(&mut self, hash: u64) {
+        let mut added = false;
+        for row in 0..ROWS {
+            let (word, shift) = self.counter(hash, row);
+            if (self.table[word] >> shift) & 0xF != 0xF {
+                self.table[word] += 1 << shift;
+                added = true;
+            }
+        }
+        if added {
+            self.additions += 1;
+            if self.additions == self.sample_size {
+                self.age();
+            }
+        }
+    }
+
+    /// Halve every counter
+    fn age(&mut self) {
+        for word in &mut self.table[..] {
+            *word = (*word >> 1) & 0x7777_7777_7777_7777;
+        }
+        self.additions /= 2;
+    }
+
+    /// Locate the word and bit offset of `hash`'s counter in `row`
+    fn counter(&self, hash: u64, row: usize) -> (usize, u32) {
+        let mut h = hash.wrapping_add(SEEDS[row]).wrapping_mul(SEEDS[row]);
+        h ^= h >> 32;
+        let index = h as usize & (self.table.len() * 16 - 1);
+        (index >> 4, (index as u32 & 0xF) * 4)
+    }
+}
+
+/// Number of independent counters per item
+const ROWS: usize = 4;
+
+const SEEDS: [u64; ROWS] = [
+    0xC3A5_C85C_97CB_3127,
+    0xB492_B66F_BE98_F273,
+    0x9AE1_6A3B_2F90_404F,
+    0xCBF2_9CE4_8422_2325,
+];
@@ -0,0 +1,179 @@
+//! LRU tracking that stores small tables inline
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{Iter, LruArray, LruSlab};
+
+/// A random-access table that maintains an LRU list, storing up to `N` elements without
+/// allocating
+///
+/// Once more than `N` elements are stored at a time, storage moves to an [`LruSlab`] on the heap
+/// and stays there. Slots and recency are preserved by the move.
+pub struct SmallLruSlab<T, const N: usize> {
+    storage: Storage<T, N>,
+}
+
+enum Storage<T, const N: usize> {
+    Inline(LruArray<T, N>),
+    // Boxed so that small tables stay small
+    Heap(Box<LruSlab<T>>),
+}
+
+impl<T, const N: usize> SmallLruSlab<T, N> {
+    /// Create an empty [`SmallLruSlab`]
+    pub const fn new() -> Self {
+        Self {
+            storage: Storage::Inline(LruArray::new()),
+        }
+    }
+
+    /// Whether elements are stored on the heap
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        match &self.storage {
+            Storage::Inline(x) => x.len(),
+            Storage::Heap(x) => x.len(),
+        }
+    }
+
+    /// Number of elements that can be stored without reallocating
+    pub fn capacity(&self) -> u32 {
+        match &self.storage {
+            Storage::Inline(_) => N as u32,
+            Storage::Heap(x) => x.capacity(),
+        }
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&mut self, value: T) -> u32 {
+        let value = match &mut self.storage {
+            Storage::Inline(x) => match x.insert(value) {
+                Ok(slot) => return slot,
+                Err(value) => value,
+            },
+            Storage::Heap(x) => return x.insert(value),
+        };
+        self.spill().insert(value)
+    }
+
+    /// Get the least recently used slot, if any
+    pub fn lru(&self) -> Option<u32> {
+        match &self.storage {
+            Storage::Inline(x) => x.lru(),
+            Storage::Heap(x) => x.lru(),
+        }
+    }
+
+    /// Remove the least recently used element, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        let slot = self.lru()?;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        match &mut self.storage {
+            Storage::Inline(x) => x.remove(slot),
+            Storage::Heap(x) => x.remove(slot),
+        }
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        match &mut self.storage {
+            Storage::Inline(x) => x.get_mut(slot),
+            Storage::Heap(x) => x.get_mut(slot),
+        }
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u32) -> Option<&T> {
+        match &self.storage {
+            Storage::Inline(x) => x.get(slot),
+            Storage::Heap(x) => x.get(slot),
+        }
+    }
+
+    /// Whether `slot` is occupied
+    pub fn contains(&self, slot: u32) -> bool {
+        self.get(slot).is_some()
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> &T {
+        self.get(slot).expect("accessing empty slot")
+    }
+
+    /// Access `slot` uniquely without marking it as most recently used
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        match &mut self.storage {
+            Storage::Inline(x) => x.peek_mut(slot),
+            Storage::Heap(x) => x.peek_mut(slot),
+        }
+    }
+
+    /// Walk the container from most to least recently used
+    pub fn iter(&self) -> Iter<'_, T> {
+        match &self.storage {
+            Storage::Inline(x) => x.iter(),
+            Storage::Heap(x) => x.iter(),
+        }
+    }
+
+    /// Move every element to the heap, preserving slots and recency
+    fn spill(&mut self) -> &mut LruSlab<T> {
+        let Storage::Inline(inline) = &mut self.storage else {
+            unreachable!("already spilled");
+        };
+        let order = inline.iter().map(|(slot, _)| slot).collect::<Vec<_>>();
+        let mut heap = LruSlab::with_capacity(2 * N as u32);
+        // Every inline slot is occupied, and a fresh slab allocates slots in ascending order
+        for slot in 0..N as u32 {
+            let new = heap.insert(inline.remove(slot));
+            debug_assert_eq!(new, slot);
+        }
+        heap.touch_many(&order);
+        self.storage = Storage::Heap(Box::new(heap));
+        let Storage::Heap(heap) = &mut self.storage else {
+            unreachable!()
+        };
+        heap
+    }
+}
+
+impl<T, const N: usize> Default for SmallLruSlab<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spill_preserves_order() {
+        let mut slab = SmallLruSlab::<char, 3>::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        let c = slab.insert('c');
+        slab.get_mut(a);
+        assert!(!slab.spilled());
+        let d = slab.insert('d');
+        assert!(slab.spilled());
+        assert_eq!(slab.peek(b), &'b');
+        let order = slab.iter().map(|(s, &x)| (s, x)).collect::<Vec<_>>();
+        assert_eq!(order, [(d, 'd'), (a, 'a'), (c, 'c'), (b, 'b')]);
+    }
+}
@@ -0,0 +1,103 @@
+//! Lock-free reads of published [`LruSlab`] versions
+
+use alloc::sync::Arc;
+use core::ops::{Deref, DerefMut};
+
+use arc_swap::ArcSwap;
+
+use crate::LruSlab;
+
+/// Exclusive owner of an [`LruSlab`] that publishes snapshots of it to [`SnapshotReader`]s
+///
+/// Mutations through [`DerefMut`] apply only to the writer's copy until [`publish`](Self::publish)
+/// is called, so readers never observe partial updates and are never blocked. Publishing clones the
+/// whole slab, so batch mutations between publications where possible.
+pub struct SnapshotWriter<T> {
+    slab: LruSlab<T>,
+    published: Arc<ArcSwap<LruSlab<T>>>,
+}
+
+impl<T: Clone> SnapshotWriter<T> {
+    /// Take ownership of `slab`, publishing its current state
+    pub fn new(slab: LruSlab<T>) -> Self {
+        Self {
+            published: Arc::new(ArcSwap::from_pointee(slab.clone())),
+            slab,
+        }
+    }
+
+    /// Make the writer's current state visible to readers
+    pub fn publish(&self) {
+        self.published.store(Arc::new(self.slab.clone()));
+    }
+}
+
+impl<T> SnapshotWriter<T> {
+    /// Create a handle for reading published snapshots
+    pub fn reader(&self) -> SnapshotReader<T> {
+        SnapshotReader {
+            published: self.published.clone(),
+        }
+    }
+
+    /// Stop publishing, returning the writer's copy of the slab
+    pub fn into_inner(self) -> LruSlab<T> {
+        self.slab
+    }
+}
+
+impl<T> Deref for SnapshotWriter<T> {
+    type Target = LruSlab<T>;
+    fn deref(&self) -> &LruSlab<T> {
+        &self.slab
+    }
+}
+
+impl<T> DerefMut for SnapshotWriter<T> {
+    fn deref_mut(&mut self) -> &mut LruSlab<T> {
+        &mut self.slab
+    }
+}
+
+/// A handle for reading the snapshots published by a [`SnapshotWriter`]
+///
+/// Loading a snapshot never waits for the writer, and the snapshot remains valid for as long as
+/// it's held, however many newer versions are published in the meantime.
+pub struct SnapshotReader<T> {
+    published: Arc<ArcSwap<LruSlab<T>>>,
+}
+
+impl<T> SnapshotReader<T> {
+    /// Get the most recently published snapshot
+    pub fn load(&self) -> Arc<LruSlab<T>> {
+        self.published.load_full()
+    }
+}
+
+impl<T> Clone for SnapshotReader<T> {
+    fn clone(&self) -> Self {
+        Self {
+            published: self.published.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publish() {
+        let mut writer = SnapshotWriter::new(LruSlab::new());
+        let reader = writer.reader();
+        let a = writer.insert('a');
+        let old = reader.load();
+        assert!(old.is_empty());
+        writer.publish();
+        writer.insert('b');
+        let new = reader.load();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new.peek(a), &'a');
+        assert!(old.is_empty());
+    }
+}
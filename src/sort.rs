@@ -0,0 +1,118 @@
+//! Reordering the recency list by comparison
+
+use core::{cmp::Ordering, mem::MaybeUninit};
+
+use allocator_api2::alloc::Allocator;
+
+use crate::{Link, LruSlab, NONE};
+
+impl<T, A: Allocator + Clone> LruSlab<T, A> {
+    /// Reorder the slots so that those comparing less are more recently used
+    ///
+    /// The sort is stable and takes O(n log n) time without allocating. Slots and pins are
+    /// unaffected.
+    pub fn sort_by(&mut self, mut compare: impl FnMut((u32, &T), (u32, &T)) -> Ordering) {
+        if self.len < 2 {
+            return;
+        }
+        let mut sorter = Sorter {
+            links: &mut self.links,
+            values: &self.values,
+            compare: &mut compare,
+        };
+        let head = sorter.sort(self.head, self.len);
+        self.head = head;
+        let mut prev = NONE;
+        let mut slot = head;
+        while slot != NONE {
+            self.link_mut(slot).prev = prev;
+            let meta = self.meta_mut(slot);
+            meta.old = false;
+            meta.hot = false;
+            prev = slot;
+            slot = self.link(slot).next;
+        }
+        self.tail = prev;
+        self.midpoint = NONE;
+        self.old_len = 0;
+        self.rebalance();
+    }
+}
+
+/// Merge sort over the `next` links of a list, leaving `prev` links stale
+struct Sorter<'a, T, F> {
+    links: &'a mut [Link],
+    values: &'a [MaybeUninit<T>],
+    compare: &'a mut F,
+}
+
+impl<T, F: FnMut((u32, &T), (u32, &T)) -> Ordering> Sorter<'_, T, F> {
+    /// Sort the `len` slots starting at `head`, returning the new head of a list ending in `NONE`
+    fn sort(&mut self, head: u32, len: u32) -> u32 {
+        if len == 1 {
+            self.links[head as usize].next = NONE;
+            return head;
+        }
+        let mut last = head;
+        for _ in 1..len / 2 {
+            last = self.links[last as usize].next;
+        }
+        let right = self.links[last as usize].next;
+        let left = self.sort(head, len / 2);
+        let right = self.sort(right, len - len / 2);
+        self.merge(left, right)
+    }
+
+    /// Merge two sorted lists ending in `NONE`, preferring `a` on ties
+    fn merge(&mut self, mut a: u32, mut b: u32) -> u32 {
+        let mut head = NONE;
+        let mut tail = NONE;
+        while a != NONE && b != NONE {
+            let values = self.values;
+            // Safety: only occupied slots are linked into the list
+            let value = |slot: u32| unsafe { values[slot as usize].assume_init_ref() };
+            let next = if (self.compare)((b, value(b)), (a, value(a))) == Ordering::Less {
+                let next = b;
+                b = self.links[b as usize].next;
+                next
+            } else {
+                let next = a;
+                a = self.links[a as usize].next;
+                next
+            };
+            self.append(&mut head, &mut tail, next);
+        }
+        let rest = if a == NONE { b } else { a };
+        self.append(&mut head, &mut tail, rest);
+        head
+    }
+
+    fn append(&mut self, head: &mut u32, tail: &mut u32, slot: u32) {
+        if *tail == NONE {
+            *head = slot;
+        } else {
+            self.links[*tail as usize].next = slot;
+        }
+        *tail = slot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn sort_by() {
+        let mut slab = [3, 1, 4, 1, 5, 9, 2, 6].into_iter().collect::<LruSlab<_>>();
+        slab.sort_by(|(_, a), (_, b)| a.cmp(b));
+        let order = slab.iter().map(|(s, &x)| (x, s)).collect::<Vec<_>>();
+        let mut expected = order.clone();
+        expected.sort_by_key(|&(x, _)| x);
+        assert_eq!(order, expected);
+        // Equal elements keep their relative order
+        assert_eq!(&order[..2], &[(1, 3), (1, 1)]);
+        slab.check_invariants().unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+//! LRU tracking in chunked storage whose elements never move
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    cell::{Cell, UnsafeCell},
+    mem::MaybeUninit,
+};
+
+use crate::NONE;
+
+/// A random-access table that maintains an LRU list, storing elements in fixed-size chunks
+///
+/// Growing allocates a new chunk rather than reallocating, so elements never move and growth never
+/// copies existing elements. Because of this, [`insert`](Self::insert) and
+/// [`touch`](Self::touch) take `&self`, so references returned by [`peek`](Self::peek) can be held
+/// across them. Removal and unique access still require `&mut self`.
+///
+/// Lookups cost one extra indirection compared to [`LruSlab`](crate::LruSlab).
+pub struct StableLruSlab<T> {
+    /// Each chunk is a leaked `Box<[Slot<T>; CHUNK_LEN]>`, so that references into it survive the
+    /// `Vec` reallocating
+    chunks: UnsafeCell<Vec<*mut [Slot<T>]>>,
+    /// Most recently used
+    head: Cell<u32>,
+    /// Least recently used
+    tail: Cell<u32>,
+    /// First unused
+    free: Cell<u32>,
+    len: Cell<u32>,
+}
+
+impl<T> StableLruSlab<T> {
+    /// Create an empty [`StableLruSlab`]
+    pub fn new() -> Self {
+        Self {
+            chunks: UnsafeCell::new(Vec::new()),
+            head: Cell::new(NONE),
+            tail: Cell::new(NONE),
+            free: Cell::new(NONE),
+            len: Cell::new(0),
+        }
+    }
+
+    /// Create a [`StableLruSlab`] that can store at least `capacity` elements without allocating
+    pub fn with_capacity(capacity: u32) -> Self {
+        let slab = Self::new();
+        while slab.capacity() < capacity {
+            slab.grow();
+        }
+        slab
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.len.get()
+    }
+
+    /// Number of elements that can be stored without allocating
+    pub fn capacity(&self) -> u32 {
+        self.chunk_count() * CHUNK_LEN
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&self, value: T) -> u32 {
+        if self.free.get() == NONE {
+            self.grow();
+        }
+        let slot = self.free.get();
+        let entry = self.slot(slot);
+        self.free.set(entry.next.get());
+        // Safety: the slot is vacant, so nothing refers to its value
+        unsafe {
+            (*entry.value.get()).write(value);
+        }
+        self.link_at_head(slot);
+        self.len.set(self.len.get() + 1);
+        slot
+    }
+
+    /// Get the least recently used slot, if any
+    pub fn lru(&self) -> Option<u32> {
+        let tail = self.tail.get();
+        (tail != NONE).then_some(tail)
+    }
+
+    /// Remove the least recently used element, returning its slot and value
+    pub fn pop_lru(&mut self) -> Option<(u32, T)> {
+        let slot = self.lru()?;
+        Some((slot, self.remove(slot)))
+    }
+
+    /// Mark `slot` as the most recently used
+    pub fn touch(&self, slot: u32) {
+        assert!(self.contains(slot), "touching empty slot");
+        if self.head.get() != slot {
+            self.unlink(slot);
+            self.link_at_head(slot);
+        }
+    }
+
+    /// Access `slot` without marking it as most recently used, if it's occupied
+    pub fn get(&self, slot: u32) -> Option<&T> {
+        // Safety: occupied values are initialized, and only mutated or dropped through `&mut self`
+        self.contains(slot)
+            .then(|| unsafe { (*self.slot(slot).value.get()).assume_init_ref() })
+    }
+
+    /// Access `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> &T {
+        self.get(slot).expect("accessing empty slot")
+    }
+
+    /// Mark `slot` as the most recently used and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        self.touch(slot);
+        // Safety: occupied values are initialized, and `&mut self` excludes other references
+        unsafe { (*self.slot(slot).value.get()).assume_init_mut() }
+    }
+
+    /// Whether `slot` is occupied
+    pub fn contains(&self, slot: u32) -> bool {
+        slot < self.capacity() && self.slot(slot).prev.get() != FREE
+    }
+
+    /// Remove the element stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        assert!(self.contains(slot), "removing empty slot");
+        self.unlink(slot);
+        let entry = self.slot(slot);
+        entry.prev.set(FREE);
+        entry.next.set(self.free.get());
+        self.free.set(slot);
+        self.len.set(self.len.get() - 1);
+        // Safety: the slot was occupied, and is now marked vacant
+        unsafe { (*entry.value.get()).assume_init_read() }
+    }
+
+    fn chunk_count(&self) -> u32 {
+        // Safety: no reference to the `Vec` outlives any method
+        unsafe { (&*self.chunks.get()).len() as u32 }
+    }
+
+    /// Add a chunk of vacant slots to the free list
+    fn grow(&self) {
+        let base = self.capacity();
+        assert!(base < u32::MAX - 2 * CHUNK_LEN, "capacity too large");
+        let free = self.free.get();
+        let chunk = (0..CHUNK_LEN)
+            .map(|i| Slot {
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                prev: Cell::new(FREE),
+                next: Cell::new(if i + 1 == CHUNK_LEN {
+                    free
+                } else {
+                    base + i + 1
+                }),
+            })
+            .collect::<Box<[_]>>();
+        // Safety: no reference to the `Vec` outlives any method
+        unsafe {
+            (&mut *self.chunks.get()).push(Box::into_raw(chunk));
+        }
+        self.free.set(base);
+    }
+
+    /// Look up a slot, which must be in bounds
+    fn slot(&self, slot: u32) -> &Slot<T> {
+        // Safety: chunks are only freed on drop, and no reference to the `Vec` outlives any method
+        unsafe {
+            let chunks = &*self.chunks.get();
+            let chunk = chunks[(slot / CHUNK_LEN) as usize];
+            &(*chunk)[(slot % CHUNK_LEN) as usize]
+        }
+    }
+
+    fn link_at_head(&self, slot: u32) {
+        let entry = self.slot(slot);
+        let head = self.head.get();
+        entry.prev.set(NONE);
+        entry.next.set(head);
+        if head == NONE {
+            self.tail.set(slot);
+        } else {
+            self.slot(head).prev.set(slot);
+        }
+        self.head.set(slot);
+    }
+
+    fn unlink(&self, slot: u32) {
+        let entry = self.slot(slot);
+        let (prev, next) = (entry.prev.get(), entry.next.get());
+        if prev == NONE {
+            self.head.set(next);
+        } else {
+            self.slot(prev).next.set(next);
+        }
+        if next == NONE {
+            self.tail.set(prev);
+        } else {
+            self.slot(next).prev.set(prev);
+        }
+    }
+}
+
+impl<T> Drop for StableLruSlab<T> {
+    fn drop(&mut self) {
+        for &chunk in self.chunks.get_mut().iter() {
+            // Safety: every chunk was leaked from a box, and is freed only here
+            let mut chunk = unsafe { Box::from_raw(chunk) };
+            for entry in chunk.iter_mut() {
+                if entry.prev.get() != FREE {
+                    // Safety: occupied values are initialized
+                    unsafe { entry.value.get_mut().assume_init_drop() }
+                }
+            }
+        }
+    }
+}
+
+impl<T> Default for StableLruSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Safety: the raw chunk pointers are uniquely owned
+unsafe impl<T: Send> Send for StableLruSlab<T> {}
+
+/// Number of slots per chunk
+const CHUNK_LEN: u32 = 256;
+
+/// `Slot::prev` of a vacant slot
+const FREE: u32 = u32::MAX - 1;
+
+struct Slot<T> {
+    /// Initialized if and only if `prev != FREE`
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// Previous slot in the LRU list, or `FREE`
+    prev: Cell<u32>,
+    /// Next slot in the LRU or free list
+    next: Cell<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_survive_growth() {
+        let mut slab = StableLruSlab::new();
+        let first = slab.insert(0);
+        let value = slab.peek(first);
+        for i in 1..1000 {
+            slab.insert(i);
+        }
+        slab.touch(first);
+        assert_eq!(*value, 0);
+        assert_eq!(slab.pop_lru(), Some((1, 1)));
+        assert_eq!(slab.remove(first), 0);
+        assert_eq!(slab.len(), 998);
+    }
+}
@@ -0,0 +1,33 @@
+//! Operation counters for an [`LruSlab`](crate::LruSlab)
+
+/// Counts of operations performed on an [`LruSlab`](crate::LruSlab) since it was created or its
+/// statistics were last reset
+///
+/// Returned by [`LruSlab::stats`](crate::LruSlab::stats).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Elements marked as most recently used by an access
+    pub hits: u64,
+    /// Elements inserted
+    pub inserts: u64,
+    /// Elements removed, including by eviction
+    pub removals: u64,
+    /// Least recently used elements removed by [`pop_lru`](crate::LruSlab::pop_lru) and the
+    /// eviction iterators
+    pub evictions: u64,
+    /// Reallocations to make room for more elements
+    pub grows: u64,
+}
+
+impl Stats {
+    /// All zeroes, usable in constant contexts
+    pub(crate) const fn new() -> Self {
+        Self {
+            hits: 0,
+            inserts: 0,
+            removals: 0,
+            evictions: 0,
+            grows: 0,
+        }
+    }
+}
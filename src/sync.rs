@@ -0,0 +1,307 @@
+//! Locked access to an [`LruSlab`] from multiple threads
+
+use alloc::boxed::Box;
+use core::ops::{Deref, DerefMut};
+
+use crate::{
+    primitives::{
+        thread_local, AtomicU32, AtomicUsize, Ordering, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    LruSlab, NONE,
+};
+
+/// An [`LruSlab`] behind a lock, accessed through guards
+///
+/// Each method holds the lock only for its own duration, except that guards returned by
+/// [`get`](Self::get), [`read`](Self::read), and [`lock`](Self::lock) hold it until dropped.
+///
+/// Shared reads through [`read`](Self::read) record their accesses in lossy per-thread buffers
+/// rather than updating the LRU list, so they never wait for each other. Buffered accesses are
+/// applied whenever the slab is next locked exclusively.
+pub struct SyncLruSlab<T> {
+    inner: RwLock<LruSlab<T>>,
+    reads: ReadBuffers,
+}
+
+impl<T> SyncLruSlab<T> {
+    /// Create an empty [`SyncLruSlab`]
+    pub fn new() -> Self {
+        Self::from(LruSlab::new())
+    }
+
+    /// Create a [`SyncLruSlab`] that can store at least `capacity` elements without reallocating
+    pub fn with_capacity(capacity: u32) -> Self {
+        Self::from(LruSlab::with_capacity(capacity))
+    }
+
+    /// Number of elements stored
+    pub fn len(&self) -> u32 {
+        self.read_lock().len()
+    }
+
+    /// Whether no elements are stored
+    pub fn is_empty(&self) -> bool {
+        self.read_lock().is_empty()
+    }
+
+    /// Insert a value, returning the slot it was stored in
+    ///
+    /// The returned slot is marked as the most recently used.
+    pub fn insert(&self, value: T) -> u32 {
+        self.lock().insert(value)
+    }
+
+    /// Remove the element stored in `slot`, returning it, if any
+    pub fn remove(&self, slot: u32) -> Option<T> {
+        let mut slab = self.lock();
+        slab.contains(slot).then(|| slab.remove(slot))
+    }
+
+    /// Remove the least recently used element that isn't pinned, returning its slot and value
+    pub fn pop_lru(&self) -> Option<(u32, T)> {
+        self.lock().pop_lru()
+    }
+
+    /// Mark `slot` as the most recently used and lock it for access, if it's occupied
+    ///
+    /// The slab remains locked until the guard is dropped.
+    pub fn get(&self, slot: u32) -> Option<Guard<'_, T>> {
+        let mut slab = self.lock();
+        if !slab.contains(slot) {
+            return None;
+        }
+        slab.get_mut(slot);
+        Some(Guard { slab, slot })
+    }
+
+    /// Record an access to `slot` and lock it for shared access, if it's occupied
+    ///
+    /// Other readers may hold the slab concurrently. The access takes effect the next time the
+    /// slab is locked exclusively, and may be lost if many accesses are recorded by the same
+    /// thread in the meantime.
+    pub fn read(&self, slot: u32) -> Option<ReadGuard<'_, T>> {
+        if self.reads.record(slot) {
+            // This thread's buffer is full, so apply it if that doesn't require waiting
+            if let Ok(mut slab) = self.inner.try_write() {
+                self.reads.drain(&mut slab);
+            }
+        }
+        let slab = self.read_lock();
+        slab.contains(slot).then_some(ReadGuard { slab, slot })
+    }
+
+    /// Lock the entire slab, applying any accesses recorded by [`read`](Self::read)
+    ///
+    /// Panics if another thread panicked while holding the lock.
+    pub fn lock(&self) -> RwLockWriteGuard<'_, LruSlab<T>> {
+        let mut slab = self.inner.write().expect("lock poisoned");
+        self.reads.drain(&mut slab);
+        slab
+    }
+
+    /// Unwrap the slab, applying any accesses recorded by [`read`](Self::read)
+    pub fn into_inner(self) -> LruSlab<T> {
+        let mut slab = self.inner.into_inner().expect("lock poisoned");
+        self.reads.drain(&mut slab);
+        slab
+    }
+
+    fn read_lock(&self) -> RwLockReadGuard<'_, LruSlab<T>> {
+        self.inner.read().expect("lock poisoned")
+    }
+}
+
+impl<T> Default for SyncLruSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<LruSlab<T>> for SyncLruSlab<T> {
+    fn from(slab: LruSlab<T>) -> Self {
+        Self {
+            inner: RwLock::new(slab),
+            reads: ReadBuffers::new(),
+        }
+    }
+}
+
+/// Exclusive access to one element of a [`SyncLruSlab`], returned by [`SyncLruSlab::get`]
+pub struct Guard<'a, T> {
+    slab: RwLockWriteGuard<'a, LruSlab<T>>,
+    slot: u32,
+}
+
+impl<T> Guard<'_, T> {
+    /// The slot being accessed
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+}
+
+impl<T> Deref for Guard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.slab.peek(self.slot)
+    }
+}
+
+impl<T> DerefMut for Guard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.slab.peek_mut(self.slot)
+    }
+}
+
+/// Shared access to one element of a [`SyncLruSlab`], returned by [`SyncLruSlab::read`]
+pub struct ReadGuard<'a, T> {
+    slab: RwLockReadGuard<'a, LruSlab<T>>,
+    slot: u32,
+}
+
+impl<T> ReadGuard<'_, T> {
+    /// The slot being accessed
+    pub fn slot(&self) -> u32 {
+        self.slot
+    }
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.slab.peek(self.slot)
+    }
+}
+
+/// Lossy buffers of recorded accesses, striped by thread to avoid contention
+struct ReadBuffers {
+    stripes: Box<[Stripe]>,
+}
+
+impl ReadBuffers {
+    fn new() -> Self {
+        Self {
+            stripes: (0..STRIPES).map(|_| Stripe::new()).collect(),
+        }
+    }
+
+    /// Record an access to `slot` in the current thread's stripe, returning whether the stripe
+    /// has filled up
+    fn record(&self, slot: u32) -> bool {
+        let stripe = &self.stripes[STRIPE.with(|&x| x) % STRIPES];
+        let position = stripe.next.fetch_add(1, Ordering::Relaxed);
+        // Overwrites the oldest access if the stripe hasn't been drained since it filled up
+        stripe.entries[position % STRIPE_LEN].store(slot, Ordering::Relaxed);
+        position % STRIPE_LEN == STRIPE_LEN - 1
+    }
+
+    /// Apply and clear all recorded accesses
+    fn drain<T>(&self, slab: &mut LruSlab<T>) {
+        let mut recorded = false;
+        for stripe in &self.stripes[..] {
+            let end = stripe.next.load(Ordering::Relaxed);
+            // Only written while the slab is locked exclusively, so can't race
+            let start = stripe.drained.load(Ordering::Relaxed);
+            if end == start {
+                continue;
+            }
+            stripe.drained.store(end, Ordering::Relaxed);
+            // Older accesses have been overwritten
+            let pending = end.wrapping_sub(start).min(STRIPE_LEN);
+            for i in 0..pending {
+                let position = end.wrapping_sub(pending).wrapping_add(i);
+                let slot = stripe.entries[position % STRIPE_LEN].swap(NONE, Ordering::Relaxed);
+                if slot != NONE {
+                    slab.record_access(slot);
+                    recorded = true;
+                }
+            }
+        }
+        if recorded {
+            slab.flush();
+        }
+    }
+}
+
+struct Stripe {
+    entries: [AtomicU32; STRIPE_LEN],
+    /// Position of the next access to record, modulo `STRIPE_LEN`
+    next: AtomicUsize,
+    /// Value of `next` when the stripe was last drained
+    drained: AtomicUsize,
+}
+
+impl Stripe {
+    fn new() -> Self {
+        Self {
+            entries: core::array::from_fn(|_| AtomicU32::new(NONE)),
+            next: AtomicUsize::new(0),
+            drained: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Number of read buffers
+const STRIPES: usize = 16;
+/// Number of accesses each read buffer holds
+///
+/// Kept tiny under loom so that models reach the overflow and drain paths.
+const STRIPE_LEN: usize = if cfg!(loom) { 2 } else { 16 };
+
+thread_local! {
+    /// Index of the current thread's read buffer, modulo `STRIPES`
+    static STRIPE: usize = next_stripe();
+}
+
+#[cfg(not(loom))]
+fn next_stripe() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Places every thread in the same stripe, exercising contention and keeping executions
+/// deterministic
+#[cfg(loom)]
+fn next_stripe() -> usize {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_access() {
+        let slab = SyncLruSlab::new();
+        let a = slab.insert(0);
+        let b = slab.insert(0);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        *slab.get(a).unwrap() += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(*slab.get(a).unwrap(), 400);
+        assert_eq!(slab.pop_lru(), Some((b, 0)));
+        assert!(slab.get(b).is_none());
+    }
+
+    #[test]
+    fn buffered_reads() {
+        let slab = SyncLruSlab::new();
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        assert_eq!(*slab.read(a).unwrap(), 'a');
+        assert_eq!(slab.lock().lru(), Some(b));
+        slab.get(b);
+        // Drained accesses aren't applied again
+        assert_eq!(slab.lock().lru(), Some(a));
+        for _ in 0..3 * STRIPE_LEN {
+            slab.read(b);
+        }
+        slab.read(a);
+        assert_eq!(slab.lock().lru(), Some(b));
+    }
+}
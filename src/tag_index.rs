@@ -0,0 +1,125 @@
+//! Fixed-capacity map from caller-provided tags to slots
+
+use alloc::boxed::Box;
+
+use crate::NONE;
+
+/// Open-addressed hash table from tags to slots, used to recognize the return of entries that are
+/// no longer resident
+///
+/// Tags are expected to be hashes already, but are mixed anyway so that sequential tags don't
+/// cluster.
+#[derive(Clone)]
+pub(crate) struct TagIndex {
+    buckets: Box<[Bucket]>,
+    /// `64 - log2(buckets.len())`
+    shift: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Bucket {
+    tag: u64,
+    /// `NONE` when vacant
+    slot: u32,
+}
+
+impl TagIndex {
+    /// Create an index that can hold at least `capacity` tags
+    pub(crate) fn new(capacity: u32) -> Self {
+        // Keep the load factor below one half, so probes stay short and always terminate. At least
+        // two buckets keep `shift` below 64.
+        let len = (capacity as usize * 2 + 1).next_power_of_two().max(2);
+        Self {
+            buckets: (0..len).map(|_| Bucket { tag: 0, slot: NONE }).collect(),
+            shift: 64 - len.trailing_zeros(),
+        }
+    }
+
+    /// Associate `tag` with `slot`, returning the slot it was previously associated with
+    pub(crate) fn insert(&mut self, tag: u64, slot: u32) -> Option<u32> {
+        debug_assert_ne!(slot, NONE);
+        let mask = self.buckets.len() - 1;
+        let mut i = self.home(tag);
+        loop {
+            let bucket = &mut self.buckets[i];
+            if bucket.slot == NONE {
+                *bucket = Bucket { tag, slot };
+                return None;
+            }
+            if bucket.tag == tag {
+                return Some(core::mem::replace(&mut bucket.slot, slot));
+            }
+            i = (i + 1) & mask;
+        }
+    }
+
+    pub(crate) fn remove(&mut self, tag: u64) -> Option<u32> {
+        let mask = self.buckets.len() - 1;
+        let mut i = self.home(tag);
+        loop {
+            let bucket = self.buckets[i];
+            if bucket.slot == NONE {
+                return None;
+            }
+            if bucket.tag == tag {
+                break;
+            }
+            i = (i + 1) & mask;
+        }
+        let slot = self.buckets[i].slot;
+        // Backward-shift deletion: pull later members of the probe sequence into the hole so
+        // lookups never need tombstones
+        let mut hole = i;
+        let mut j = i;
+        loop {
+            j = (j + 1) & mask;
+            let bucket = self.buckets[j];
+            if bucket.slot == NONE {
+                break;
+            }
+            let home = self.home(bucket.tag);
+            // Move `bucket` into the hole unless its home lies cyclically in (hole, j]
+            if (j.wrapping_sub(home) & mask) >= (j.wrapping_sub(hole) & mask) {
+                self.buckets[hole] = bucket;
+                hole = j;
+            }
+        }
+        self.buckets[hole].slot = NONE;
+        Some(slot)
+    }
+
+    fn home(&self, tag: u64) -> usize {
+        (tag.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> self.shift) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_capacity() {
+        let mut index = TagIndex::new(0);
+        assert_eq!(index.remove(7), None);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut index = TagIndex::new(8);
+        for tag in 0..8 {
+            assert_eq!(index.insert(tag, tag as u32), None);
+        }
+        assert_eq!(index.insert(3, 30), Some(3));
+        for tag in (0..8).step_by(2) {
+            assert!(index.remove(tag).is_some());
+        }
+        for tag in 0..8 {
+            let expected = match tag {
+                3 => Some(30),
+                _ if tag % 2 == 0 => None,
+                _ => Some(tag as u32),
+            };
+            assert_eq!(index.remove(tag), expected);
+        }
+    }
+}
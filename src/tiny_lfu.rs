@@ -0,0 +1,274 @@
+//! Window TinyLFU admission
+
+use alloc::boxed::Box;
+
+use crate::{
+    list::{Link, Links, List},
+    sketch::FrequencySketch,
+};
+
+/// A fixed-capacity table that evicts according to the W-TinyLFU policy
+///
+/// New entries enter a small LRU admission window. Entries leaving the window must compete for a
+/// place in the main segmented LRU: a compact sketch estimates how often each entry's tag has been
+/// seen recently, and the window's candidate only displaces the main victim if its tag is more
+/// popular. Entries that are used while on probation in the main region are protected from
+/// eviction until they fall out of the protected segment.
+///
+/// Tags are supplied by the caller on insert and should identify the value, e.g. by hashing its
+/// key, so that popularity is remembered across evictions.
+pub struct TinyLfuSlab<T> {
+    slots: Box<[Slot<T>]>,
+    free: List,
+    /// Recently inserted entries
+    window: List,
+    /// Main entries that haven't been used since they were admitted or demoted
+    probation: List,
+    /// Main entries that have been used since they were admitted
+    protected: List,
+    sketch: FrequencySketch,
+    window_capacity: u32,
+    protected_capacity: u32,
+}
+
+impl<T> TinyLfuSlab<T> {
+    /// Create a [`TinyLfuSlab`] that holds at most `capacity` values
+    ///
+    /// One percent of the capacity, and at least one slot, is devoted to the admission window. Of
+    /// the remainder, 80% may be protected.
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity != 0, "capacity must be nonzero");
+        assert!(capacity != u32::MAX, "capacity too large");
+        let mut slots = (0..capacity)
+            .map(|_| Slot {
+                value: None,
+                tag: 0,
+                link: Link::UNLINKED,
+                queue: Queue::Free,
+            })
+            .collect::<Box<[_]>>();
+        let mut free = List::EMPTY;
+        for slot in 0..capacity {
+            free.push_back(&mut slots[..], slot);
+        }
+        let window_capacity = (capacity / 100).max(1);
+        let main = capacity - window_capacity;
+        Self {
+            slots,
+            free,
+            window: List::EMPTY,
+            probation: List::EMPTY,
+            protected: List::EMPTY,
+            sketch: FrequencySketch::new(capacity),
+            window_capacity,
+            // Computed in 64 bits to avoid overflow
+            protected_capacity: (u64::from(main) * 8 / 10) as u32,
+        }
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values stored
+    pub fn len(&self) -> u32 {
+        self.window.len + self.probation.len + self.protected.len
+    }
+
+    /// Maximum number of values that can be stored
+    pub fn capacity(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Insert a value identified by `tag`, returning the slot it was stored in and the slot and
+    /// value of any entry evicted to make room
+    ///
+    /// The evicted entry may be an older entry, if the new entry's tag is popular enough to
+    /// displace it, or the least recently inserted entry in the admission window otherwise.
+    pub fn insert(&mut self, tag: u64, value: T) -> (u32, Option<(u32, T)>) {
+        self.sketch.increment(tag);
+        let evicted = match self.len() == self.capacity() {
+            true => Some(self.evict()),
+            false => None,
+        };
+        let slot = self
+            .free
+            .pop_front(&mut self.slots[..])
+            .expect("no free slots");
+        let entry = &mut self.slots[slot as usize];
+        debug_assert!(entry.value.is_none(), "corrupt free list");
+        entry.value = Some(value);
+        entry.tag = tag;
+        entry.queue = Queue::Window;
+        self.window.push_front(&mut self.slots[..], slot);
+        if self.window.len > self.window_capacity {
+            // There's room in the main region, so admission is unconditional
+            let candidate = self.window.pop_back(&mut self.slots[..]).unwrap();
+            self.probation.push_front(&mut self.slots[..], candidate);
+            self.slots[candidate as usize].queue = Queue::Probation;
+        }
+        (slot, evicted)
+    }
+
+    /// Remove the value stored in `slot`, returning it
+    pub fn remove(&mut self, slot: u32) -> T {
+        self.unlink(slot);
+        self.release(slot)
+    }
+
+    /// Record a use of `slot` and access it uniquely
+    pub fn get_mut(&mut self, slot: u32) -> &mut T {
+        let entry = &self.slots[slot as usize];
+        self.sketch.increment(entry.tag);
+        match entry.queue {
+            Queue::Window => {
+                self.window.remove(&mut self.slots[..], slot);
+                self.window.push_front(&mut self.slots[..], slot);
+            }
+            Queue::Probation => {
+                self.probation.remove(&mut self.slots[..], slot);
+                self.protect(slot);
+            }
+            Queue::Protected => {
+                self.protected.remove(&mut self.slots[..], slot);
+                self.protected.push_front(&mut self.slots[..], slot);
+            }
+            Queue::Free => panic!("accessing empty slot"),
+        }
+        self.peek_mut(slot)
+    }
+
+    /// Access `slot` without recording a use
+    pub fn peek(&self, slot: u32) -> &T {
+        self.slots[slot as usize].value.as_ref().unwrap()
+    }
+
+    /// Access `slot` uniquely without recording a use
+    pub fn peek_mut(&mut self, slot: u32) -> &mut T {
+        self.slots[slot as usize].value.as_mut().unwrap()
+    }
+
+    /// Make room for one entry in the admission window
+    fn evict(&mut self) -> (u32, T) {
+        let victim = self.probation.back().or(self.protected.back());
+        let slot = match (self.window.len >= self.window_capacity, victim) {
+            (true, Some(victim)) => {
+                let candidate = self.window.back().unwrap();
+                let candidate_frequency = self.sketch.frequency(self.slots[candidate as usize].tag);
+                let victim_frequency = self.sketch.frequency(self.slots[victim as usize].tag);
+                self.window.remove(&mut self.slots[..], candidate);
+                if candidate_frequency > victim_frequency {
+                    self.unlink(victim);
+                    self.probation.push_front(&mut self.slots[..], candidate);
+                    self.slots[candidate as usize].queue = Queue::Probation;
+                    victim
+                } else {
+                    candidate
+                }
+            }
+            (true, None) => self.window.pop_back(&mut self.slots[..]).unwrap(),
+            (false, Some(victim)) => {
+                // The window has shrunk from removals, so the main region is over capacity
+                self.unlink(victim);
+                victim
+            }
+            (false, None) => unreachable!("evicting from empty table"),
+        };
+        (slot, self.release(slot))
+    }
+
+    /// Move an unlinked main entry into the protected segment
+    fn protect(&mut self, slot: u32) {
+        self.protected.push_front(&mut self.slots[..], slot);
+        self.slots[slot as usize].queue = Queue::Protected;
+        if self.protected.len > self.protected_capacity {
+            let demoted = self.protected.pop_back(&mut self.slots[..]).unwrap();
+            self.probation.push_front(&mut self.slots[..], demoted);
+            self.slots[demoted as usize].queue = Queue::Probation;
+        }
+    }
+
+    fn unlink(&mut self, slot: u32) {
+        match self.slots[slot as usize].queue {
+            Queue::Window => self.window.remove(&mut self.slots[..], slot),
+            Queue::Probation => self.probation.remove(&mut self.slots[..], slot),
+            Queue::Protected => self.protected.remove(&mut self.slots[..], slot),
+            Queue::Free => panic!("removing empty slot"),
+        }
+    }
+
+    /// Return an unlinked slot to the free list, yielding its value
+    fn release(&mut self, slot: u32) -> T {
+        let entry = &mut self.slots[slot as usize];
+        entry.queue = Queue::Free;
+        let value = entry.value.take().unwrap();
+        self.free.push_front(&mut self.slots[..], slot);
+        value
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    tag: u64,
+    link: Link,
+    queue: Queue,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Queue {
+    Free,
+    Window,
+    Probation,
+    Protected,
+}
+
+impl<T> Links for [Slot<T>] {
+    fn link(&mut self, slot: u32) -> &mut Link {
+        &mut self[slot as usize].link
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn popular_survives_scan() {
+        let mut cache = TinyLfuSlab::new(4);
+        let hot = [0, 1, 2].map(|tag| cache.insert(tag, tag).0);
+        for _ in 0..4 {
+            for slot in hot {
+                cache.get_mut(slot);
+            }
+        }
+        for tag in 3..30 {
+            let (_, evicted) = cache.insert(tag, tag);
+            assert!(evicted.is_none_or(|(_, x)| x >= 3));
+        }
+        for (tag, slot) in hot.into_iter().enumerate() {
+            assert_eq!(*cache.peek(slot), tag as u64);
+        }
+    }
+
+    #[test]
+    fn admits_popular_newcomer() {
+        let mut cache = TinyLfuSlab::new(2);
+        let (a, _) = cache.insert(0, 'a');
+        cache.insert(1, 'b');
+        cache.get_mut(a);
+        cache.get_mut(a);
+        // 'b' leaves the window, but is less popular than 'a'
+        let (_, evicted) = cache.insert(2, 'c');
+        assert_eq!(evicted.map(|(_, x)| x), Some('b'));
+        let (_, evicted) = cache.insert(3, 'd');
+        assert_eq!(evicted.map(|(_, x)| x), Some('c'));
+        // Repeated misses make 'd' popular enough to displace 'a'
+        for _ in 0..2 {
+            let (_, evicted) = cache.insert(3, 'd');
+            assert_eq!(evicted.map(|(_, x)| x), Some('d'));
+        }
+        let (_, evicted) = cache.insert(3, 'd');
+        assert_eq!(evicted, Some((a, 'a')));
+    }
+}
@@ -0,0 +1,178 @@
+//! Traces of the accesses made to an [`LruSlab`], and their analysis and replay
+
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use allocator_api2::alloc::Allocator;
+
+use crate::LruSlab;
+
+/// One access in a trace
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TraceEvent {
+    /// Logical time of the access, increasing through the trace
+    pub time: u64,
+    /// Slot accessed
+    pub slot: u32,
+    /// How the slot was accessed
+    pub kind: TraceKind,
+}
+
+/// How a slot was accessed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TraceKind {
+    /// A new element was stored in the slot
+    Insert,
+    /// The element in the slot was used
+    Touch,
+    /// The element in the slot was removed
+    Remove,
+}
+
+/// An operation on an [`LruSlab`], for replay by [`LruSlab::apply`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<T> {
+    /// [`insert`](LruSlab::insert) a value
+    Insert(T),
+    /// Mark a slot as used, as if by [`get_mut`](LruSlab::get_mut)
+    Touch(u32),
+    /// [`remove`](LruSlab::remove) the element in a slot, dropping it
+    Remove(u32),
+}
+
+impl<T: Default> From<TraceEvent> for Op<T> {
+    /// Replay an event, inserting default values
+    fn from(event: TraceEvent) -> Self {
+        match event.kind {
+            TraceKind::Insert => Self::Insert(T::default()),
+            TraceKind::Touch => Self::Touch(event.slot),
+            TraceKind::Remove => Self::Remove(event.slot),
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> LruSlab<T, A> {
+    /// Perform each of `ops` in order
+    ///
+    /// Slabs in the same state end up in the same state after the same operations, so replaying a
+    /// recorded trace onto an equivalent slab reproduces the slots and order of the original.
+    pub fn apply(&mut self, ops: impl IntoIterator<Item = Op<T>>) {
+        for op in ops {
+            match op {
+                Op::Insert(value) => {
+                    self.insert(value);
+                }
+                Op::Touch(slot) => {
+                    self.get_mut(slot);
+                }
+                Op::Remove(slot) => {
+                    self.remove(slot);
+                }
+            }
+        }
+    }
+}
+
+/// The hit ratio a clairvoyant cache holding `capacity` elements would achieve on `trace`
+///
+/// Each insertion is a request for a new element, which always misses, and each touch is a
+/// request for the element in that slot. Upon a miss when full, the cache evicts whichever
+/// element will be requested furthest in the future (Belady's algorithm), which is optimal, so
+/// this bounds the hit ratio of any eviction policy. Removed elements leave the cache. Takes
+/// `n log n` time in the length of the trace.
+pub fn optimal_hit_ratio(trace: &[TraceEvent], capacity: u32) -> f64 {
+    const NEVER: usize = usize::MAX;
+    let slots = trace.iter().map(|e| e.slot as usize + 1).max().unwrap_or(0);
+    // Index of the next request for the element involved in each event
+    let mut next = alloc::vec![NEVER; trace.len()];
+    let mut next_use = alloc::vec![NEVER; slots];
+    for (i, event) in trace.iter().enumerate().rev() {
+        let slot = event.slot as usize;
+        match event.kind {
+            TraceKind::Insert => {
+                next[i] = next_use[slot];
+                // Earlier events concern a previous occupant
+                next_use[slot] = NEVER;
+            }
+            TraceKind::Touch => {
+                next[i] = next_use[slot];
+                next_use[slot] = i;
+            }
+            TraceKind::Remove => next_use[slot] = NEVER,
+        }
+    }
+
+    // Cached elements by when they're next requested, and that time for each cached slot
+    let mut cache = BTreeSet::new();
+    let mut cached: Vec<Option<usize>> = alloc::vec![None; slots];
+    let mut hits = 0u64;
+    let mut requests = 0u64;
+    for (i, event) in trace.iter().enumerate() {
+        let slot = event.slot as usize;
+        let was_cached = match cached[slot].take() {
+            Some(key) => cache.remove(&(key, event.slot)),
+            None => false,
+        };
+        match event.kind {
+            TraceKind::Remove => continue,
+            TraceKind::Touch => hits += u64::from(was_cached),
+            TraceKind::Insert => {}
+        }
+        requests += 1;
+        cache.insert((next[i], event.slot));
+        cached[slot] = Some(next[i]);
+        if cache.len() > capacity as usize {
+            let (_, evicted) = cache.pop_last().unwrap();
+            cached[evicted as usize] = None;
+        }
+    }
+    match requests {
+        0 => 0.0,
+        n => hits as f64 / n as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn belady() {
+        use TraceKind::*;
+        let trace = [
+            (0, Insert),
+            (1, Insert),
+            (0, Touch),
+            (1, Touch),
+            (0, Touch),
+            (1, Remove),
+            (1, Insert),
+            (0, Touch),
+        ]
+        .into_iter()
+        .zip(0..)
+        .map(|((slot, kind), time)| TraceEvent { time, slot, kind })
+        .collect::<Vec<_>>();
+        assert_eq!(optimal_hit_ratio(&trace, 1), 3.0 / 7.0);
+        assert_eq!(optimal_hit_ratio(&trace, 2), 4.0 / 7.0);
+        assert_eq!(optimal_hit_ratio(&trace, 0), 0.0);
+    }
+
+    #[test]
+    fn replay() {
+        let mut slab = LruSlab::new();
+        slab.apply([
+            Op::Insert('a'),
+            Op::Insert('b'),
+            Op::Touch(0),
+            Op::Remove(1),
+            Op::Insert('c'),
+        ]);
+        assert!(slab.iter().eq([(1, &'c'), (0, &'a')]));
+        let event = TraceEvent {
+            time: 0,
+            slot: 3,
+            kind: TraceKind::Touch,
+        };
+        assert_eq!(Op::<()>::from(event), Op::Touch(3));
+    }
+}
@@ -0,0 +1,90 @@
+//! JavaScript bindings for an [`LruSlab`] of arbitrary values
+
+use wasm_bindgen::prelude::*;
+
+use crate::LruSlab;
+
+/// A table of JavaScript values that maintains an LRU list in constant time
+///
+/// Exported to JavaScript as `LruSlab`. Lookups of vacant slots yield `undefined`.
+#[wasm_bindgen(js_name = LruSlab)]
+pub struct JsLruSlab {
+    inner: LruSlab<JsValue>,
+}
+
+#[wasm_bindgen(js_class = LruSlab)]
+impl JsLruSlab {
+    /// Create an empty table
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: LruSlab::new(),
+        }
+    }
+
+    /// Number of values stored
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> u32 {
+        self.inner.len()
+    }
+
+    /// Whether no values are stored
+    #[wasm_bindgen(js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Store `value`, returning the slot it was stored in, which becomes the most recently used
+    pub fn insert(&mut self, value: JsValue) -> u32 {
+        self.inner.insert(value)
+    }
+
+    /// Mark `slot` as the most recently used and get its value
+    pub fn get(&mut self, slot: u32) -> JsValue {
+        if !self.inner.contains(slot) {
+            return JsValue::UNDEFINED;
+        }
+        self.inner.get_mut(slot).clone()
+    }
+
+    /// Get the value of `slot` without marking it as most recently used
+    pub fn peek(&self, slot: u32) -> JsValue {
+        self.inner.get(slot).cloned().unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Mark `slot` as the most recently used, returning whether it's occupied
+    pub fn touch(&mut self, slot: u32) -> bool {
+        if !self.inner.contains(slot) {
+            return false;
+        }
+        self.inner.get_mut(slot);
+        true
+    }
+
+    /// The least recently used slot, if any
+    pub fn lru(&self) -> Option<u32> {
+        self.inner.lru()
+    }
+
+    /// Vacate `slot`, returning its value
+    pub fn remove(&mut self, slot: u32) -> JsValue {
+        if !self.inner.contains(slot) {
+            return JsValue::UNDEFINED;
+        }
+        self.inner.remove(slot)
+    }
+
+    /// Remove the least recently used value, returning it
+    pub fn evict(&mut self) -> JsValue {
+        match self.inner.pop_lru() {
+            Some((_, value)) => value,
+            None => JsValue::UNDEFINED,
+        }
+    }
+}
+
+impl Default for JsLruSlab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+//! Verifies that no operation of `CheckedLruSlab` can panic
+//!
+//! `no_panic` fails to link any function it can't prove panic-free, which requires optimization,
+//! so this only checks anything in release builds: `cargo test --release --test no_panic`. The
+//! features that may panic by design are excluded.
+#![cfg(all(
+    feature = "alloc",
+    not(debug_assertions),
+    not(any(feature = "strict", feature = "trace", feature = "tracing"))
+))]
+
+use lru_slab::{CheckedLruSlab, InsertError, ReserveError, VacantError};
+use no_panic::no_panic;
+
+type Slab = CheckedLruSlab<u64>;
+
+#[no_panic]
+fn with_capacity(capacity: u32) -> Result<Slab, ReserveError> {
+    Slab::with_capacity(capacity)
+}
+
+#[no_panic]
+fn reserve(slab: &mut Slab, additional: u32) -> Result<(), ReserveError> {
+    slab.reserve(additional)
+}
+
+#[no_panic]
+fn insert(slab: &mut Slab, value: u64) -> Result<u32, InsertError<u64>> {
+    slab.insert(value)
+}
+
+#[no_panic]
+fn remove(slab: &mut Slab, slot: u32) -> Result<u64, VacantError> {
+    slab.remove(slot)
+}
+
+#[no_panic]
+fn lru(slab: &Slab) -> Option<u32> {
+    slab.lru()
+}
+
+#[no_panic]
+fn pop_lru(slab: &mut Slab) -> Option<(u32, u64)> {
+    slab.pop_lru()
+}
+
+#[no_panic]
+fn contains(slab: &Slab, slot: u32) -> bool {
+    slab.contains(slot)
+}
+
+#[no_panic]
+fn get(slab: &Slab, slot: u32) -> Result<u64, VacantError> {
+    slab.get(slot).copied()
+}
+
+#[no_panic]
+fn get_mut(slab: &mut Slab, slot: u32) -> Result<u64, VacantError> {
+    slab.get_mut(slot).map(|x| *x)
+}
+
+#[no_panic]
+fn peek_mut(slab: &mut Slab, slot: u32) -> Result<u64, VacantError> {
+    slab.peek_mut(slot).map(|x| *x)
+}
+
+#[no_panic]
+fn sum(slab: &Slab) -> u64 {
+    slab.iter().map(|(_, &x)| x).sum()
+}
+
+#[test]
+fn no_panic() {
+    let mut slab = with_capacity(2).unwrap();
+    reserve(&mut slab, 4).unwrap();
+    let a = insert(&mut slab, 1).unwrap();
+    let b = insert(&mut slab, 2).unwrap();
+    assert!(contains(&slab, a));
+    assert_eq!(get_mut(&mut slab, a), Ok(1));
+    assert_eq!(peek_mut(&mut slab, b), Ok(2));
+    assert_eq!(lru(&slab), Some(b));
+    assert_eq!(sum(&slab), 3);
+    assert_eq!(remove(&mut slab, b), Ok(2));
+    assert_eq!(get(&slab, b), Err(VacantError { slot: b }));
+    assert_eq!(pop_lru(&mut slab), Some((a, 1)));
+}